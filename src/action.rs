@@ -0,0 +1,64 @@
+/// Something the player can do, bound to one or more keys
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Hold,
+    Pause,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateCW,
+        Action::RotateCCW,
+        Action::Hold,
+        Action::Pause,
+        Action::Quit,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::SoftDrop => "Soft drop",
+            Action::HardDrop => "Hard drop",
+            Action::RotateCW => "Rotate clockwise",
+            Action::RotateCCW => "Rotate counterclockwise",
+            Action::Hold => "Hold",
+            Action::Pause => "Pause",
+            Action::Quit => "Quit",
+        }
+    }
+
+    /// Name used in config file
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCW => "rotate_cw",
+            Action::RotateCCW => "rotate_ccw",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn by_config_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.config_name() == name)
+    }
+}
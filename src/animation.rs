@@ -0,0 +1,172 @@
+use std::time::SystemTime;
+use crossterm::style::Color;
+use crate::{board::Board, generic, point::Point};
+
+const ROW_FLASH_MS: u128 = 300;
+const ROW_FLASH_FRAME_MS: u128 = 75;
+const LOCK_FLASH_MS: u128 = 100;
+const CALLOUT_MS: u128 = 1500;
+
+pub enum AnimationKind {
+    /// Cleared rows flash, then board is redrawn without them
+    RowFlash(Vec<i16>),
+    /// Blocks of a locked piece flash, then return to their color
+    LockFlash(Vec<Point>),
+    /// Text shown right of the board
+    Callout(String),
+}
+
+struct Animation {
+    kind: AnimationKind,
+    start: SystemTime,
+    duration_ms: u128,
+    /// Last drawn frame
+    frame: Option<u128>,
+}
+
+/// Runs animations a frame at a time, so they never block the game loop
+pub struct Animator {
+    animations: Vec<Animation>,
+    callout_x: u16,
+    callout_y: u16,
+}
+
+impl Animator {
+    pub fn new(callout_x: u16, callout_y: u16) -> Animator {
+        Animator {
+            animations: Vec::new(),
+            callout_x,
+            callout_y,
+        }
+    }
+
+    pub fn add(&mut self, kind: AnimationKind, board: &Board) {
+        let duration_ms = match kind {
+            AnimationKind::RowFlash(_) => ROW_FLASH_MS,
+            AnimationKind::LockFlash(_) => LOCK_FLASH_MS,
+            AnimationKind::Callout(_) => {
+                // Only show newest callout
+                let (callouts, animations): (Vec<Animation>, Vec<Animation>) = std::mem::take(&mut self.animations)
+                    .into_iter()
+                    .partition(|animation| matches!(animation.kind, AnimationKind::Callout(_)));
+                self.animations = animations;
+                for callout in &callouts {
+                    self.finish(callout, board);
+                }
+                CALLOUT_MS
+            },
+        };
+
+        self.animations.push(Animation {
+            kind,
+            start: SystemTime::now(),
+            duration_ms,
+            frame: None,
+        });
+    }
+
+    /// Row flash is running, so board shouldn't be redrawn yet
+    pub fn is_flashing_rows(&self) -> bool {
+        self.animations.iter().any(|animation| matches!(animation.kind, AnimationKind::RowFlash(_)))
+    }
+
+    /// Draw current frame of animations and remove finished ones
+    ///
+    /// Returns true if board should be redrawn
+    pub fn update(&mut self, board: &Board) -> bool {
+        let mut redraw = false;
+
+        for animation in &mut self.animations {
+            let elapsed = animation.start.elapsed().unwrap().as_millis();
+            if elapsed >= animation.duration_ms {
+                continue;
+            }
+
+            let frame = match animation.kind {
+                AnimationKind::RowFlash(_) => elapsed / ROW_FLASH_FRAME_MS,
+                _ => 0,
+            };
+            if animation.frame == Some(frame) {
+                continue;
+            }
+
+            animation.frame = Some(frame);
+            Animator::draw(animation, frame, board, (self.callout_x, self.callout_y));
+        }
+
+        let (finished, running): (Vec<Animation>, Vec<Animation>) = std::mem::take(&mut self.animations)
+            .into_iter()
+            .partition(|animation| animation.start.elapsed().unwrap().as_millis() >= animation.duration_ms);
+        self.animations = running;
+
+        for animation in &finished {
+            redraw |= self.finish(animation, board);
+        }
+
+        // Rows are still flashing, so wait with redraw
+        redraw && !self.is_flashing_rows()
+    }
+
+    /// Finish all animations right away
+    ///
+    /// Returns true if board should be redrawn
+    pub fn finish_all(&mut self, board: &Board) -> bool {
+        let mut redraw = false;
+        for animation in &std::mem::take(&mut self.animations) {
+            redraw |= self.finish(animation, board);
+        }
+
+        redraw
+    }
+
+    fn draw(animation: &Animation, frame: u128, board: &Board, (callout_x, callout_y): (u16, u16)) {
+        match &animation.kind {
+            AnimationKind::RowFlash(rows) => {
+                generic::set_color(Color::White);
+                let glyph = if frame.is_multiple_of(2) { board.get_block_str(None).to_string() } else { board.get_empty_str() };
+                for y in rows {
+                    for x in 0..board.get_width() as i16 {
+                        board.print_cell(x, *y, &glyph);
+                    }
+                }
+            },
+            AnimationKind::LockFlash(points) => {
+                generic::set_color(Color::White);
+                for point in points {
+                    board.print_cell(point.x, point.y, board.get_block_str(None));
+                }
+            },
+            AnimationKind::Callout(text) => {
+                generic::set_color(Color::White);
+                generic::move_cursor(callout_x, callout_y);
+                print!("{}", text);
+            },
+        }
+
+        generic::set_color(Color::Reset);
+        generic::move_cursor(0, 0);
+    }
+
+    /// Clean up after animation
+    ///
+    /// Returns true if board should be redrawn
+    fn finish(&self, animation: &Animation, board: &Board) -> bool {
+        match &animation.kind {
+            AnimationKind::RowFlash(_) => return true,
+            AnimationKind::LockFlash(points) => {
+                for point in points {
+                    generic::set_color(point.color);
+                    board.print_cell(point.x, point.y, board.get_block_str(point.kind));
+                }
+                generic::set_color(Color::Reset);
+            },
+            AnimationKind::Callout(text) => {
+                generic::move_cursor(self.callout_x, self.callout_y);
+                print!("{}", " ".repeat(text.chars().count()));
+            },
+        }
+
+        generic::move_cursor(0, 0);
+        false
+    }
+}
@@ -1,19 +1,40 @@
-use crate::{generic, point};
+use crossterm::style::Color;
+use crate::{generic, config::Config, theme::{self, Theme}, tet::{Tet, TetKind}, piece_set};
+
+/// Color of garbage blocks, which aren't part of any piece
+pub const GARBAGE_COLOR: Color = Color::DarkGrey;
 
 pub struct Board {
     width: u16,
     height: u16,
     offset_x: u16,
     offset_y: u16,
+    x_width: u16,
+    theme: Theme,
+    block_str: String,
+    ghost_str: String,
+    /// Glyph per piece kind, if pieces are told apart by pattern
+    pattern_strs: Option<Vec<String>>,
+    /// Piece colors, indexed by `TetKind::index`
+    colors: Vec<Color>,
 }
 
 impl Board {
-    pub fn new() -> Board {
+    pub fn new(config: &Config, offset_x: u16, offset_y: u16) -> Board {
         Board {
-            width: 10,
-            height: 20,
-            offset_x: 5,
-            offset_y: 2
+            width: config.display.board_width,
+            height: config.display.board_height,
+            offset_x,
+            offset_y,
+            x_width: config.display.x_width,
+            theme: config.get_theme(),
+            block_str: theme::fit_glyph(config.get_theme().block, config.display.x_width),
+            ghost_str: theme::fit_glyph(config.get_theme().ghost, config.display.x_width),
+            pattern_strs: match config.display.piece_patterns {
+                true => Some(theme::PIECE_PATTERNS.iter().map(|glyph| theme::fit_glyph(glyph, config.display.x_width)).collect()),
+                false => None,
+            },
+            colors: TetKind::all().iter().map(|kind| config.piece_color(*kind)).collect(),
         }
     }
 
@@ -25,41 +46,103 @@ impl Board {
         self.width
     }
 
-    pub fn get_offset_x(&self) -> u16 {
-        self.offset_x
+    /// Terminal column of board column `x`
+    pub fn get_screen_x(&self, x: i16) -> u16 {
+        (x * self.x_width as i16) as u16 + self.offset_x
+    }
+
+    /// Text printed for a single block of `kind`, `x_width` wide
+    pub fn get_block_str(&self, kind: Option<TetKind>) -> &str {
+        match (&self.pattern_strs, kind) {
+            (Some(pattern_strs), Some(kind)) => &pattern_strs[kind.index() % pattern_strs.len()],
+            _ => &self.block_str,
+        }
+    }
+
+    pub fn get_color(&self, kind: TetKind) -> Color {
+        self.colors[kind.index()]
+    }
+
+    /// Text printed for a single block of the ghost piece, `x_width` wide
+    pub fn get_ghost_str(&self) -> &str {
+        &self.ghost_str
+    }
+
+    /// Text printed to remove a single block, `x_width` wide
+    pub fn get_empty_str(&self) -> String {
+        " ".repeat(self.x_width as usize)
+    }
+
+    /// Print `glyph` at board position, unless it's above the board
+    pub fn print_cell(&self, x: i16, y: i16, glyph: &str) {
+        if y < 0 || generic::headless() {
+            return;
+        }
+
+        generic::move_cursor(self.get_screen_x(x), y as u16 + self.offset_y);
+        print!("{}", glyph);
     }
 
-    pub fn get_offset_y(&self) -> u16 {
-        self.offset_y
+    /// Print small Tet of `kind` lying flat, or clear the space if none
+    pub fn print_preview(&self, kind: Option<TetKind>, x: u16, y: u16) {
+        if generic::headless() {
+            return;
+        }
+
+        let (width, height) = piece_set::get().get_preview_size();
+        let empty_str = " ".repeat((width * self.x_width) as usize);
+        for row in 0..height {
+            generic::move_cursor(x, y + row);
+            print!("{}", empty_str);
+        }
+
+        let Some(kind) = kind else {
+            return;
+        };
+
+        generic::set_color(self.get_color(kind));
+        for point in Tet::of_kind(kind).preview_points() {
+            generic::move_cursor(x + point.x as u16 * self.x_width, y + point.y as u16);
+            print!("{}", self.get_block_str(Some(kind)));
+        }
+        generic::set_color(Color::Reset);
     }
 
     pub fn draw_frame(&self) {
+        if generic::headless() {
+            return;
+        }
+
         generic::move_cursor(0, 0);
 
         for y in 0..self.height {
             generic::move_cursor(self.offset_x - 2, y + self.offset_y);
-            print!("<|");
+            print!("{}", self.theme.frame_left);
 
-            generic::move_cursor(self.offset_x + self.width * point::X_WIDTH as u16, y + self.offset_y);
-            print!("|>");
+            generic::move_cursor(self.offset_x + self.width * self.x_width, y + self.offset_y);
+            print!("{}", self.theme.frame_right);
         }
 
-        for x in 0..self.width * point::X_WIDTH as u16 {
+        for x in 0..self.width * self.x_width {
             generic::move_cursor(x + self.offset_x, self.offset_y - 1);
-            print!("_");
+            print!("{}", self.theme.frame_top);
 
             generic::move_cursor(x + self.offset_x, self.height + self.offset_y);
-            print!("‾");
+            print!("{}", self.theme.frame_bottom);
         }
 
         generic::move_cursor(0, 0);
     }
 
     pub fn clear_board(&self) {
+        if generic::headless() {
+            return;
+        }
+
         generic::move_cursor(0, 0);
 
         for y in self.offset_y..self.height + self.offset_y {
-            for x in self.offset_x..self.width * point::X_WIDTH as u16 + self.offset_x {
+            for x in self.offset_x..self.width * self.x_width + self.offset_x {
                 generic::move_cursor(x, y);
                 print!(" ");
             }
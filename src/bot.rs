@@ -0,0 +1,312 @@
+use std::{collections::VecDeque, time::SystemTime};
+use crate::{action::Action, game_controller::GameController, tet::{Tet, TetKind}};
+
+const HEIGHT_WEIGHT: f32 = -0.51;
+const LINES_WEIGHT: f32 = 0.76;
+const HOLES_WEIGHT: f32 = -0.36;
+const BUMPINESS_WEIGHT: f32 = -0.18;
+const WELLS_WEIGHT: f32 = -0.05;
+
+/// Scores cells where a Tet landed, where `None` rules out the placement
+type ScoreFn<'a> = &'a dyn Fn(&[(i16, i16)]) -> Option<f32>;
+
+/// Where to place the falling Tet, and how to get it there
+struct Placement {
+    hold: bool,
+    /// Clockwise turns, or -1 for one counterclockwise turn
+    turns: i8,
+    /// Columns to move, negative for left
+    shift: i16,
+    score: f32,
+}
+
+/// Chooses actions for a game, instead of a player at the keyboard
+pub trait Autoplayer {
+    /// Called before each game
+    fn new_game(&mut self) -> Result<(), String>;
+
+    /// Actions to pass to `GameController::step` this cycle
+    fn next_actions(&mut self, game: &GameController) -> Result<Vec<Action>, String>;
+
+    /// Called when the game ends
+    fn game_over(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Plays the game through the same actions as a player
+pub struct Bot {
+    /// Actions left to place current Tet
+    plan: VecDeque<Action>,
+    /// Pieces placed when plan was made, so a new plan is made for each piece
+    plan_pieces: u32,
+    /// Time between each action, or 0 to do the whole plan in one step
+    action_interval_ms: u128,
+    action_time: SystemTime,
+}
+
+impl Autoplayer for Bot {
+    fn new_game(&mut self) -> Result<(), String> {
+        self.plan.clear();
+        self.plan_pieces = 0;
+        Ok(())
+    }
+
+    fn next_actions(&mut self, game: &GameController) -> Result<Vec<Action>, String> {
+        if self.plan.is_empty() || self.plan_pieces != game.get_stats().pieces {
+            self.plan = Bot::find_plan(game);
+            self.plan_pieces = game.get_stats().pieces;
+        }
+
+        Ok(take_actions(&mut self.plan, self.action_interval_ms, &mut self.action_time))
+    }
+}
+
+/// Take actions from `plan`, one at a time every `interval_ms`, or all at once if it's 0
+pub fn take_actions(plan: &mut VecDeque<Action>, interval_ms: u128, action_time: &mut SystemTime) -> Vec<Action> {
+    if interval_ms == 0 {
+        return plan.drain(..).collect();
+    }
+
+    if action_time.elapsed().unwrap().as_millis() < interval_ms {
+        return Vec::new();
+    }
+
+    *action_time = SystemTime::now();
+    plan.pop_front().into_iter().collect()
+}
+
+impl Bot {
+    pub fn new(action_interval_ms: u128) -> Bot {
+        Bot {
+            plan: VecDeque::new(),
+            plan_pieces: 0,
+            action_interval_ms,
+            action_time: SystemTime::now(),
+        }
+    }
+
+    /// Actions placing the falling Tet, or the held Tet, where it scores best
+    fn find_plan(game: &GameController) -> VecDeque<Action> {
+        let grid = Bot::get_grid(game);
+        let score = |cells: &[(i16, i16)]| Bot::evaluate(cells, &grid);
+
+        let mut best = match game.get_tet() {
+            Some(tet) => Bot::best_placement(tet, false, game, &grid, &score),
+            None => return VecDeque::new(),
+        };
+
+        if let Some(kind) = Bot::get_hold_kind(game) {
+            let held = Bot::best_placement(&Tet::new(kind, game), true, game, &grid, &score);
+            if held.as_ref().map(|placement| placement.score) > best.as_ref().map(|placement| placement.score) {
+                best = held;
+            }
+        }
+
+        match best {
+            Some(placement) => Bot::get_actions(&placement),
+            // Topped out whatever it does
+            None => VecDeque::from([Action::HardDrop]),
+        }
+    }
+
+    /// Actions placing the falling Tet, or the held Tet if `hold`, at `cells`
+    ///
+    /// Returns `None` if the cells can't be reached by rotating, moving and hard dropping, so tucks and spins aren't planned
+    pub fn plan_placement(game: &GameController, hold: bool, cells: &[(i16, i16)]) -> Option<VecDeque<Action>> {
+        let grid = Bot::get_grid(game);
+        let mut target = cells.to_vec();
+        target.sort();
+
+        let tet = match hold {
+            true => Tet::new(Bot::get_hold_kind(game)?, game),
+            false => game.get_tet()?.clone(),
+        };
+
+        let matches = |cells: &[(i16, i16)]| {
+            let mut cells = cells.to_vec();
+            cells.sort();
+            if cells == target { Some(0.0) } else { None }
+        };
+        Bot::best_placement(&tet, hold, game, &grid, &matches).map(|placement| Bot::get_actions(&placement))
+    }
+
+    /// Kind that would be played if hold is used, if hold is allowed
+    fn get_hold_kind(game: &GameController) -> Option<TetKind> {
+        if game.is_hold_used() {
+            return None;
+        }
+
+        game.get_hold().or(game.get_queue().front().copied())
+    }
+
+    fn get_actions(placement: &Placement) -> VecDeque<Action> {
+        let mut actions = VecDeque::new();
+        if placement.hold {
+            actions.push_back(Action::Hold);
+        }
+        match placement.turns {
+            -1 => actions.push_back(Action::RotateCCW),
+            turns => actions.extend((0..turns).map(|_| Action::RotateCW)),
+        }
+        let shift_action = if placement.shift < 0 { Action::MoveLeft } else { Action::MoveRight };
+        actions.extend((0..placement.shift.abs()).map(|_| shift_action));
+        actions.push_back(Action::HardDrop);
+
+        actions
+    }
+
+    /// Try every rotation of `tet`, moved to every column it can reach, and dropped
+    ///
+    /// Only placements reached by rotating, then moving sideways, then hard dropping are found.
+    /// Tucking under overhangs or spinning into slots after a soft drop isn't tried, so T-spins are never planned
+    fn best_placement(tet: &Tet, hold: bool, game: &GameController, grid: &[Vec<bool>], score: ScoreFn) -> Option<Placement> {
+        let mut best: Option<Placement> = None;
+
+        for turns in [0, 1, 2, -1] {
+            let mut rotated = Some(tet.clone());
+            if turns == -1 {
+                rotated = tet.rotated(false, game);
+            }
+            for _ in 0..turns {
+                rotated = rotated.and_then(|rotated| rotated.rotated(true, game));
+            }
+            let Some(rotated) = rotated else {
+                continue;
+            };
+
+            for direction in [-1, 1] {
+                let mut shifted = rotated.clone();
+                let mut shift = 0;
+
+                loop {
+                    // Unshifted placement is tried when moving left
+                    if direction == -1 || shift != 0 {
+                        if let Some(score) = score(&Bot::drop_cells(&shifted, grid)) {
+                            if best.as_ref().is_none_or(|best| score > best.score) {
+                                best = Some(Placement { hold, turns, shift, score });
+                            }
+                        }
+                    }
+
+                    let step = direction * shifted.get_step();
+                    if game.collision_check(&shifted.points_pos(), step, 0) {
+                        break;
+                    }
+                    shifted.pos.x += step;
+                    shift += direction;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Occupied cells of board, by row and column
+    fn get_grid(game: &GameController) -> Vec<Vec<bool>> {
+        (0..game.occupied.get_height() as i16)
+            .map(|y| (0..game.occupied.get_width() as i16).map(|x| game.occupied.get(x, y).is_some()).collect())
+            .collect()
+    }
+
+    /// Cells of `tet` after dropping it
+    fn drop_cells(tet: &Tet, grid: &[Vec<bool>]) -> Vec<(i16, i16)> {
+        let height = grid.len() as i16;
+        let points = tet.points_pos();
+
+        let is_free = |x: i16, y: i16| y < 0 || (y < height && !grid[y as usize][x as usize]);
+        let step = tet.get_step();
+        let mut distance = 0;
+        while points.iter().all(|point| is_free(point.x, point.y + distance + step)) {
+            distance += step;
+        }
+
+        points.iter().map(|point| (point.x, point.y + distance)).collect()
+    }
+
+    /// Score board with `cells` added, or `None` if any are above the board
+    ///
+    /// Cells are looked up on top of `grid` instead of copying it, since this runs for every candidate placement
+    fn evaluate(cells: &[(i16, i16)], grid: &[Vec<bool>]) -> Option<f32> {
+        if cells.iter().any(|(_, y)| *y < 0) {
+            return None;
+        }
+
+        let width = grid[0].len();
+        let is_occupied = |x: usize, y: usize| grid[y][x] || cells.contains(&(x as i16, y as i16));
+
+        // Rows left after full rows are cleared, from the top down
+        let rows: Vec<usize> = (0..grid.len()).filter(|y| !(0..width).all(|x| is_occupied(x, *y))).collect();
+        let lines = (grid.len() - rows.len()) as i16;
+
+        let heights: Vec<i16> = (0..width)
+            .map(|x| match rows.iter().position(|y| is_occupied(x, *y)) {
+                Some(index) => (rows.len() - index) as i16,
+                None => 0,
+            })
+            .collect();
+
+        let aggregate_height: i16 = heights.iter().sum();
+        let holes = (0..width)
+            .map(|x| rows.iter().skip_while(|y| !is_occupied(x, **y)).filter(|y| !is_occupied(x, **y)).count() as i16)
+            .sum::<i16>();
+        let bumpiness: i16 = heights.windows(2).map(|pair| (pair[0] - pair[1]).abs()).sum();
+        let wells: i16 = (0..width)
+            .map(|x| {
+                let left = if x == 0 { i16::MAX } else { heights[x - 1] };
+                let right = if x == width - 1 { i16::MAX } else { heights[x + 1] };
+                (left.min(right) - heights[x]).max(0)
+            })
+            .sum();
+
+        Some(
+            HEIGHT_WEIGHT * aggregate_height as f32
+                + LINES_WEIGHT * lines as f32
+                + HOLES_WEIGHT * holes as f32
+                + BUMPINESS_WEIGHT * bumpiness as f32
+                + WELLS_WEIGHT * wells as f32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, generic};
+
+    /// 4 by 4 grid with the bottom row filled except the right column
+    fn gap_grid() -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![false; 4]; 4];
+        grid[3] = vec![true, true, true, false];
+        grid
+    }
+
+    #[test]
+    fn filling_gap_beats_covering_it() {
+        let grid = gap_grid();
+
+        let filled = Bot::evaluate(&[(3, 3), (3, 2)], &grid).unwrap();
+        let covered = Bot::evaluate(&[(2, 2), (3, 2)], &grid).unwrap();
+
+        // The cleared line leaves one block on an empty board
+        assert!((filled - (HEIGHT_WEIGHT + LINES_WEIGHT + BUMPINESS_WEIGHT)).abs() < 0.001);
+        assert!(filled > covered);
+        assert!(Bot::evaluate(&[(3, 0), (3, -1)], &grid).is_none());
+    }
+
+    #[test]
+    fn placement_is_planned_only_if_reachable_by_hard_drop() {
+        generic::set_headless(true);
+        let mut game = GameController::new(&Config::default());
+        game.set_sequence(&[TetKind::by_name("O").unwrap()]);
+        game.step(&[]);
+
+        let left_wall = [(0, 18), (1, 18), (0, 19), (1, 19)];
+        let expected: VecDeque<Action> = [Action::MoveLeft; 4].into_iter().chain([Action::HardDrop]).collect();
+        assert!(Bot::plan_placement(&game, false, &left_wall) == Some(expected));
+
+        // Under an overhang, which needs a tuck
+        game.set_occupied(&[(0, 17, None), (1, 17, None)]);
+        assert!(Bot::plan_placement(&game, false, &left_wall).is_none());
+    }
+}
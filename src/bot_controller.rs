@@ -0,0 +1,95 @@
+use std::time::SystemTime;
+use crossterm::event::KeyCode;
+use crate::{generic, bot::Autoplayer, config::Config, game_controller::GameController, input_controller::InputController, action::Action};
+
+/// Time between bot actions in demo, so it can be followed
+pub const DEMO_ACTION_MS: u128 = 60;
+/// Headless games end after this many pieces, since the bot rarely tops out
+const BENCHMARK_MAX_PIECES: u32 = 10_000;
+
+/// Let `bot` play until it tops out, or ESC or quit is pressed
+pub fn demo_loop(config: &Config, input_controller: &mut InputController, bot: &mut dyn Autoplayer, title: &str) {
+    let mut game = GameController::new(config);
+    game.print_message(4, &format!("{}   ESC: Stop", title));
+
+    if let Err(error) = bot.new_game() {
+        generic::error_print(&error);
+        return;
+    }
+
+    loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) || input_controller.action_pressed(Action::Quit) {
+            return;
+        }
+
+        let actions = match bot.next_actions(&game) {
+            Ok(actions) => actions,
+            Err(error) => {
+                generic::clear_terminal();
+                generic::error_print(&error);
+                return;
+            },
+        };
+        if !game.step(&actions) {
+            break;
+        }
+
+        input_controller.end_update();
+    }
+
+    let _ = bot.game_over();
+    game.print_message(2, "Game over");
+    game.print_message(4, &format!("{:width$}", "Press ENTER", width = title.len() + 13));
+    InputController::wait_for_enter();
+}
+
+/// Play `games` games with `bot` without drawing, and print results
+pub fn benchmark(config: &Config, games: u32, bot: &mut dyn Autoplayer) -> Result<(), String> {
+    let mut config = config.clone();
+    config.display.animations = false;
+    generic::set_headless(true);
+
+    let start_time = SystemTime::now();
+    let mut total_pieces = 0;
+    let mut total_lines = 0;
+
+    for i in 0..games {
+        let mut game = GameController::new(&config);
+        bot.new_game()?;
+
+        let topped_out = loop {
+            let actions = bot.next_actions(&game)?;
+            if !game.step(&actions) {
+                break true;
+            }
+            if game.get_stats().pieces >= BENCHMARK_MAX_PIECES {
+                break false;
+            }
+        };
+        bot.game_over()?;
+
+        let stats = game.get_stats();
+        println!(
+            "Game {}: {} pieces, {} lines, score {}{}",
+            i + 1,
+            stats.pieces,
+            stats.lines,
+            game.get_score(),
+            if topped_out { ", topped out" } else { "" }
+        );
+        total_pieces += stats.pieces;
+        total_lines += stats.lines;
+    }
+
+    let seconds = start_time.elapsed().unwrap().as_secs_f32();
+    println!(
+        "Average: {:.1} pieces, {:.1} lines, {:.0} pieces per second",
+        total_pieces as f32 / games as f32,
+        total_lines as f32 / games as f32,
+        total_pieces as f32 / seconds
+    );
+
+    Ok(())
+}
@@ -0,0 +1,654 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::atomic::{AtomicBool, Ordering}};
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{tet::{self, TetKind}, piece_set, action::Action, key::Key, theme::{self, Theme, THEMES}, layout::LayoutConfig, randomizer::RANDOMIZERS};
+
+const FILE: &str = "config.toml";
+/// Board widths allowed by config, and by puzzles which set their own width
+pub const MIN_BOARD_WIDTH: u16 = 4;
+pub const MAX_BOARD_WIDTH: u16 = 40;
+
+/// Config file couldn't be parsed, so writing it would replace the user's settings with defaults
+static WRITE_BLOCKED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub gameplay: GameplayConfig,
+    pub display: DisplayConfig,
+    pub keys: KeysConfig,
+    /// Actions that repeat while held, by config name of action
+    pub repeat: BTreeMap<String, RepeatConfig>,
+    /// Piece colors overriding those of the theme and palette, by piece name like "T"
+    pub colors: BTreeMap<String, String>,
+    /// Panels around the board, by game mode
+    pub layouts: BTreeMap<String, LayoutConfig>,
+    pub versus: VersusConfig,
+    pub net: NetConfig,
+    pub spectate: SpectateConfig,
+    pub dig: DigConfig,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameplayConfig {
+    /// Higher value makes fall speed increase slower per level
+    pub level_scale: u8,
+    /// Score needed per level
+    pub level_at_score: u16,
+    /// Time between each automatic fall at level 1
+    pub initial_fall_ms: u16,
+    /// Number of upcoming pieces shown
+    pub preview_count: u8,
+    /// Level at start of game
+    pub start_level: u8,
+    /// "classic" or "srs"
+    pub rotation: String,
+    /// "random" for any piece at any time, or "bag" to deal every piece of the set before repeating
+    pub randomizer: String,
+    /// "standard", or name of a piece set file in the pieces folder, like "pentominoes"
+    pub pieces: String,
+    /// Pieces are twice as large, with each cell covering 2 by 2 cells of the board
+    pub big: bool,
+    /// Game ends when a piece locks with any cell above the visible board, not only when all of them are
+    pub partial_lock_out: bool,
+    /// Cycles per second of game and menu loops when idle, which sleep until input arrives or the next cycle is due
+    ///
+    /// Only limits how often loops run, since gravity and key repeat are timed by the clock
+    pub tick_rate: u16,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Terminal columns used per board column
+    pub x_width: u16,
+    pub board_width: u16,
+    pub board_height: u16,
+    /// Name of built-in theme, like "classic" or "ascii"
+    pub theme: String,
+    /// Show where the falling piece will land
+    pub ghost: bool,
+    /// "theme" to use theme colors, or a colorblind-safe palette like "deuteranopia" or "protanopia"
+    pub palette: String,
+    /// Draw each piece kind with its own glyph, so pieces don't rely on color alone
+    pub piece_patterns: bool,
+    /// Flash cleared rows and locked pieces, and show callouts for tetrises and T-spins
+    pub animations: bool,
+}
+
+/// Keys bound to each action, like "Left" or "Ctrl+Z"
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub move_left: Vec<String>,
+    pub move_right: Vec<String>,
+    pub soft_drop: Vec<String>,
+    pub hard_drop: Vec<String>,
+    pub rotate_cw: Vec<String>,
+    pub rotate_ccw: Vec<String>,
+    pub hold: Vec<String>,
+    pub pause: Vec<String>,
+    pub quit: Vec<String>,
+}
+
+/// Keys of each player in local versus
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VersusConfig {
+    pub player_1: KeysConfig,
+    pub player_2: KeysConfig,
+}
+
+/// Last used settings of online versus
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetConfig {
+    /// Port to host on
+    pub port: u16,
+    /// Host and port to join, like "127.0.0.1:7878"
+    pub address: String,
+}
+
+/// Publishing of marathon games for spectators
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpectateConfig {
+    /// Let spectators watch with `--spectate <address>`
+    pub publish: bool,
+    /// Address to publish on, like "127.0.0.1:7879"
+    pub address: String,
+}
+
+/// Dig mode, where garbage rows are cleared as fast as possible
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DigConfig {
+    /// Garbage rows at start
+    pub rows: u16,
+    /// Chance in percent that the hole of a garbage row moves from the hole of the row below
+    pub messiness: u8,
+    /// Time between each garbage row rising from below, or 0 for none
+    pub rise_secs: u16,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RepeatConfig {
+    /// Time from initial press until first repeat
+    pub delay_ms: u16,
+    /// Time between each following repeat
+    pub interval_ms: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut repeat = BTreeMap::new();
+        for action in [Action::MoveLeft, Action::MoveRight, Action::SoftDrop] {
+            repeat.insert(String::from(action.config_name()), RepeatConfig::default());
+        }
+
+        Config {
+            gameplay: GameplayConfig::default(),
+            display: DisplayConfig::default(),
+            keys: KeysConfig::default(),
+            repeat,
+            colors: BTreeMap::new(),
+            layouts: BTreeMap::from([
+                (String::from("marathon"), LayoutConfig::default_for("marathon")),
+                (String::from("versus"), LayoutConfig::default_for("versus")),
+                (String::from("dig"), LayoutConfig::default_for("dig")),
+            ]),
+            versus: VersusConfig::default(),
+            net: NetConfig::default(),
+            spectate: SpectateConfig::default(),
+            dig: DigConfig::default(),
+        }
+    }
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        GameplayConfig {
+            level_scale: 5,
+            level_at_score: 150,
+            initial_fall_ms: 750,
+            preview_count: 3,
+            start_level: 1,
+            rotation: String::from("classic"),
+            randomizer: String::from("random"),
+            pieces: String::from("standard"),
+            big: false,
+            partial_lock_out: false,
+            tick_rate: 120,
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            x_width: 2,
+            board_width: 10,
+            board_height: 20,
+            theme: String::from("classic"),
+            ghost: true,
+            palette: String::from("theme"),
+            piece_patterns: false,
+            animations: true,
+        }
+    }
+}
+
+impl Default for VersusConfig {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|name| String::from(*name)).collect();
+
+        VersusConfig {
+            player_1: KeysConfig {
+                move_left: keys(&["a"]),
+                move_right: keys(&["d"]),
+                soft_drop: keys(&["s"]),
+                hard_drop: keys(&["w"]),
+                rotate_cw: keys(&["e"]),
+                rotate_ccw: keys(&["q"]),
+                hold: keys(&["Tab"]),
+                pause: keys(&["p"]),
+                quit: keys(&["Esc"]),
+            },
+            player_2: KeysConfig {
+                move_left: keys(&["Left"]),
+                move_right: keys(&["Right"]),
+                soft_drop: keys(&["Down"]),
+                hard_drop: keys(&["Enter"]),
+                rotate_cw: keys(&["Up"]),
+                rotate_ccw: keys(&["."]),
+                hold: keys(&["-"]),
+                pause: Vec::new(),
+                quit: Vec::new(),
+            },
+        }
+    }
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        NetConfig {
+            port: 7878,
+            address: String::from("127.0.0.1:7878"),
+        }
+    }
+}
+
+impl Default for SpectateConfig {
+    fn default() -> Self {
+        SpectateConfig {
+            publish: false,
+            address: String::from("127.0.0.1:7879"),
+        }
+    }
+}
+
+impl Default for DigConfig {
+    fn default() -> Self {
+        DigConfig {
+            rows: 10,
+            messiness: 100,
+            rise_secs: 0,
+        }
+    }
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        RepeatConfig {
+            delay_ms: 100,
+            interval_ms: 100,
+        }
+    }
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|name| String::from(*name)).collect();
+
+        KeysConfig {
+            move_left: keys(&["Left"]),
+            move_right: keys(&["Right"]),
+            soft_drop: keys(&["Down"]),
+            hard_drop: keys(&["Space"]),
+            rotate_cw: keys(&["Up", "x"]),
+            rotate_ccw: keys(&["z"]),
+            hold: keys(&["c"]),
+            pause: keys(&["p"]),
+            quit: keys(&["Esc"]),
+        }
+    }
+}
+
+impl Config {
+    /// Reset invalid config values to their defaults, keeping the rest
+    ///
+    /// Returns all problems found
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+        let default = Config::default();
+
+        check_range(&mut errors, "gameplay.level_scale", &mut self.gameplay.level_scale, default.gameplay.level_scale, 1, 100);
+        check_range(&mut errors, "gameplay.level_at_score", &mut self.gameplay.level_at_score, default.gameplay.level_at_score, 1, 10_000);
+        check_range(&mut errors, "gameplay.initial_fall_ms", &mut self.gameplay.initial_fall_ms, default.gameplay.initial_fall_ms, 50, 5_000);
+        check_range(&mut errors, "gameplay.preview_count", &mut self.gameplay.preview_count, default.gameplay.preview_count, 0, 6);
+        check_range(&mut errors, "gameplay.start_level", &mut self.gameplay.start_level, default.gameplay.start_level, 1, 30);
+        check_range(&mut errors, "gameplay.tick_rate", &mut self.gameplay.tick_rate, default.gameplay.tick_rate, 30, 1_000);
+        check_range(&mut errors, "net.port", &mut self.net.port, default.net.port, 1, 65_535);
+        check_range(&mut errors, "display.x_width", &mut self.display.x_width, default.display.x_width, 1, 4);
+        check_range(&mut errors, "display.board_width", &mut self.display.board_width, default.display.board_width, MIN_BOARD_WIDTH as u32, MAX_BOARD_WIDTH as u32);
+        check_range(&mut errors, "display.board_height", &mut self.display.board_height, default.display.board_height, 16, 40);
+        if self.gameplay.big && !self.allows_big() {
+            errors.push(String::from("gameplay.big needs an even display.board_width, and is turned off"));
+            self.gameplay.big = false;
+        }
+        // Leave room for Tets to spawn above the garbage
+        let max_dig_rows = self.display.board_height - 4;
+        check_range(&mut errors, "dig.rows", &mut self.dig.rows, default.dig.rows.min(max_dig_rows), 1, max_dig_rows as u32);
+        check_range(&mut errors, "dig.messiness", &mut self.dig.messiness, default.dig.messiness, 0, 100);
+        check_range(&mut errors, "dig.rise_secs", &mut self.dig.rise_secs, default.dig.rise_secs, 0, 60);
+
+        for (name, keys) in [("keys", &mut self.keys), ("versus.player_1", &mut self.versus.player_1), ("versus.player_2", &mut self.versus.player_2)] {
+            for action in Action::ALL {
+                keys.get_mut(action).retain(|key| {
+                    let known = Key::parse(key).is_some();
+                    if !known {
+                        errors.push(format!("{}.{} has unknown key \"{}\", which is ignored", name, action.config_name(), key));
+                    }
+                    known
+                });
+            }
+        }
+
+        self.repeat.retain(|name, _| {
+            let known = Action::by_config_name(name).is_some();
+            if !known {
+                errors.push(format!("repeat.{} is not an action, and is ignored", name));
+            }
+            known
+        });
+        let default_repeat = RepeatConfig::default();
+        for (name, repeat) in &mut self.repeat {
+            check_range(&mut errors, &format!("repeat.{}.delay_ms", name), &mut repeat.delay_ms, default_repeat.delay_ms, 0, 2_000);
+            check_range(&mut errors, &format!("repeat.{}.interval_ms", name), &mut repeat.interval_ms, default_repeat.interval_ms, 0, 1_000);
+        }
+
+        if !tet::ROTATION_SYSTEMS.contains(&self.gameplay.rotation.as_str()) {
+            errors.push(format!("gameplay.rotation \"{}\" is unknown, use one of: {}", self.gameplay.rotation, tet::ROTATION_SYSTEMS.join(", ")));
+            self.gameplay.rotation = default.gameplay.rotation;
+        }
+
+        if !RANDOMIZERS.contains(&self.gameplay.randomizer.as_str()) {
+            errors.push(format!("gameplay.randomizer \"{}\" is unknown, use one of: {}", self.gameplay.randomizer, RANDOMIZERS.join(", ")));
+            self.gameplay.randomizer = default.gameplay.randomizer;
+        }
+
+        if Theme::by_name(&self.display.theme).is_none() {
+            let names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+            errors.push(format!("display.theme \"{}\" is unknown, use one of: {}", self.display.theme, names.join(", ")));
+            self.display.theme = default.display.theme;
+        }
+
+        if self.display.palette != "theme" && theme::palette_by_name(&self.display.palette).is_none() {
+            let names: Vec<&str> = theme::PALETTES.iter().map(|(name, _)| *name).collect();
+            errors.push(format!("display.palette \"{}\" is unknown, use \"theme\" or one of: {}", self.display.palette, names.join(", ")));
+            self.display.palette = default.display.palette;
+        }
+
+        self.colors.retain(|name, color| {
+            if parse_color(color).is_some() {
+                return true;
+            }
+            errors.push(format!("colors.{} has unknown color \"{}\" (use a name like \"dark_yellow\" or \"#rrggbb\")", name, color));
+            false
+        });
+
+        errors
+    }
+
+    /// Repeat timing of `action`, if it repeats while held
+    pub fn get_repeat(&self, action: Action) -> Option<&RepeatConfig> {
+        self.repeat.get(action.config_name())
+    }
+
+    /// Repeat timing of `action`, added with default timing if missing
+    pub fn get_repeat_mut(&mut self, action: Action) -> &mut RepeatConfig {
+        self.repeat.entry(String::from(action.config_name())).or_default()
+    }
+
+    /// Big pieces move 2 columns at a time, so they can only reach both walls of an even board width
+    pub fn allows_big(&self) -> bool {
+        self.display.board_width.is_multiple_of(2)
+    }
+
+    /// Layout for game `mode`, or default layout of mode if not configured
+    pub fn get_layout(&self, mode: &str) -> LayoutConfig {
+        self.layouts.get(mode).cloned().unwrap_or_else(|| LayoutConfig::default_for(mode))
+    }
+
+    pub fn get_theme(&self) -> Theme {
+        Theme::by_name(&self.display.theme).unwrap_or(THEMES[0])
+    }
+
+    /// Color of piece from colors config, or from palette, piece set or theme if not set
+    pub fn piece_color(&self, kind: TetKind) -> Color {
+        if let Some(color) = self.colors.get(kind.name()).and_then(|color| parse_color(color)) {
+            return color;
+        }
+
+        match theme::palette_by_name(&self.display.palette) {
+            Some(colors) => colors[kind.index() % colors.len()],
+            None => piece_set::get().get_color(kind).unwrap_or(self.get_theme().piece_color(kind)),
+        }
+    }
+}
+
+impl KeysConfig {
+    pub fn get(&self, action: Action) -> &Vec<String> {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::SoftDrop => &self.soft_drop,
+            Action::HardDrop => &self.hard_drop,
+            Action::RotateCW => &self.rotate_cw,
+            Action::RotateCCW => &self.rotate_ccw,
+            Action::Hold => &self.hold,
+            Action::Pause => &self.pause,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut Vec<String> {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::SoftDrop => &mut self.soft_drop,
+            Action::HardDrop => &mut self.hard_drop,
+            Action::RotateCW => &mut self.rotate_cw,
+            Action::RotateCCW => &mut self.rotate_ccw,
+            Action::Hold => &mut self.hold,
+            Action::Pause => &mut self.pause,
+            Action::Quit => &mut self.quit,
+        }
+    }
+
+    /// Parsed keys bound to `action`, skipping invalid ones
+    pub fn get_keys(&self, action: Action) -> Vec<Key> {
+        self.get(action).iter().filter_map(|key| Key::parse(key)).collect()
+    }
+
+    /// Bind `key` to `action`, and unbind it from any other action
+    pub fn bind(&mut self, action: Action, key: Key) {
+        for other in Action::ALL {
+            self.get_mut(other).retain(|name| Key::parse(name) != Some(key));
+        }
+
+        self.get_mut(action).push(key.name());
+    }
+}
+
+/// Load config file, or write default config file if it doesn't exist
+///
+/// Returns config and any problems found. Invalid values are replaced by their defaults, and if the file can't be parsed
+/// at all, the default config is returned and the file isn't written until fixed
+pub fn load() -> (Config, Vec<String>) {
+    if !Path::new(FILE).exists() {
+        let config = Config::default();
+        let errors = match write(&config) {
+            Ok(_) => Vec::new(),
+            Err(error) => vec![error],
+        };
+        return (config, errors);
+    }
+
+    let config_str = match fs::read_to_string(FILE) {
+        Ok(config_str) => config_str,
+        Err(_) => return (Config::default(), vec![format!("Failed to read from config file {}", FILE)]),
+    };
+
+    let table: toml::Table = match config_str.parse() {
+        Ok(table) => table,
+        Err(error) => {
+            WRITE_BLOCKED.store(true, Ordering::Relaxed);
+            return (Config::default(), vec![format!("Invalid config file {}, which won't be changed until fixed:\n{}", FILE, error)]);
+        },
+    };
+
+    let (mut config, mut errors) = from_table(table);
+    errors.extend(config.validate());
+
+    (config, errors)
+}
+
+/// Config of values in `table`, using defaults for values that don't fit their setting
+///
+/// Returns config and the values that were left out
+fn from_table(mut table: toml::Table) -> (Config, Vec<String>) {
+    migrate(&mut table);
+    let mut merged = toml::Table::try_from(Config::default()).expect("Default config serializes");
+    let mut errors = Vec::new();
+
+    // Settings are tried one at a time, so one bad value doesn't discard the rest of the file
+    for (section, value) in table {
+        let values = match (value, merged.get(&section)) {
+            (toml::Value::Table(fields), Some(toml::Value::Table(_))) => fields.into_iter().map(|(field, value)| (Some(field), value)).collect(),
+            (value, _) => vec![(None, value)],
+        };
+
+        for (field, value) in values {
+            let mut candidate = merged.clone();
+            let name = match &field {
+                Some(field) => {
+                    if let Some(toml::Value::Table(fields)) = candidate.get_mut(&section) {
+                        fields.insert(field.clone(), value);
+                    }
+                    format!("{}.{}", section, field)
+                },
+                None => {
+                    candidate.insert(section.clone(), value);
+                    section.clone()
+                },
+            };
+
+            match toml::Value::Table(candidate.clone()).try_into::<Config>() {
+                Ok(_) => merged = candidate,
+                Err(error) => errors.push(format!("{} is invalid, using default: {}", name, error.message())),
+            }
+        }
+    }
+
+    let config = toml::Value::Table(merged).try_into().expect("Config of valid values deserializes");
+    (config, errors)
+}
+
+/// Move settings of older versions in `table` to where they are now
+///
+/// `gameplay.repeat_ms` was both delay and interval of moving sideways, unless repeat timing of moves is already set
+fn migrate(table: &mut toml::Table) {
+    let Some(toml::Value::Table(gameplay)) = table.get_mut("gameplay") else {
+        return;
+    };
+    let Some(repeat_ms) = gameplay.remove("repeat_ms") else {
+        return;
+    };
+    let toml::Value::Table(repeat) = table.entry("repeat").or_insert_with(|| toml::Value::Table(toml::Table::new())) else {
+        return;
+    };
+
+    for action in [Action::MoveLeft, Action::MoveRight] {
+        repeat.entry(action.config_name()).or_insert_with(|| toml::Value::Table(toml::Table::from_iter([
+            (String::from("delay_ms"), repeat_ms.clone()),
+            (String::from("interval_ms"), repeat_ms.clone()),
+        ])));
+    }
+}
+
+/// Write `config` to the config file, unless the file couldn't be parsed when loaded
+pub fn write(config: &Config) -> Result<(), String> {
+    if WRITE_BLOCKED.load(Ordering::Relaxed) {
+        return Err(format!("Not saving settings, because config file {} has errors that must be fixed first", FILE));
+    }
+
+    let config_str = toml::to_string_pretty(config).map_err(|_| String::from("Failed to serialize config"))?;
+
+    if fs::write(FILE, config_str).is_err() {
+        return Err(format!("Failed to write to config file {}", FILE));
+    }
+
+    Ok(())
+}
+
+/// Reset `value` to `default` if it's outside `min` to `max`
+fn check_range<T: Copy + Into<u32>>(errors: &mut Vec<String>, name: &str, value: &mut T, default: T, min: u32, max: u32) {
+    let current: u32 = (*value).into();
+    if current < min || current > max {
+        errors.push(format!("{} must be between {} and {}, but is {}, using {}", name, min, max, current, default.into()));
+        *value = default;
+    }
+}
+
+/// Parse color name like "dark_yellow", or hex color like "#ff8800"
+pub fn parse_color(color: &str) -> Option<Color> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb {
+            r: (value >> 16) as u8,
+            g: (value >> 8) as u8,
+            b: value as u8,
+        });
+    }
+
+    Color::try_from(color).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_values_reset_only_themselves() {
+        let table: toml::Table = "[gameplay]\nstart_level = 5\npreview_count = \"x\"\nrotation = \"bogus\"\n\n[display]\nboard_height = 3\nghost = false\n\n[keys]\nhold = [\"c\", \"NotAKey\"]\n"
+            .parse()
+            .unwrap();
+
+        let (mut config, mut errors) = from_table(table);
+        errors.extend(config.validate());
+
+        assert_eq!(errors.len(), 4);
+        assert_eq!(config.gameplay.start_level, 5);
+        assert_eq!(config.gameplay.preview_count, Config::default().gameplay.preview_count);
+        assert_eq!(config.gameplay.rotation, "classic");
+        assert_eq!(config.display.board_height, 20);
+        assert!(!config.display.ghost);
+        assert_eq!(config.keys.hold, vec![String::from("c")]);
+    }
+
+    #[test]
+    fn unknown_section_is_reported() {
+        let table: toml::Table = "[gamplay]\nstart_level = 5\n".parse().unwrap();
+
+        let (config, errors) = from_table(table);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.gameplay.start_level, 1);
+    }
+
+    #[test]
+    fn repeat_ms_moves_to_sideways_repeat() {
+        let table: toml::Table = "[gameplay]\nrepeat_ms = 50\n\n[repeat.move_right]\ndelay_ms = 150\ninterval_ms = 30\n".parse().unwrap();
+
+        let (mut config, mut errors) = from_table(table);
+        errors.extend(config.validate());
+
+        assert!(errors.is_empty());
+        let left = config.get_repeat(Action::MoveLeft).unwrap();
+        assert_eq!((left.delay_ms, left.interval_ms), (50, 50));
+        let right = config.get_repeat(Action::MoveRight).unwrap();
+        assert_eq!((right.delay_ms, right.interval_ms), (150, 30));
+        assert_eq!(config.get_repeat(Action::SoftDrop).unwrap().interval_ms, RepeatConfig::default().interval_ms);
+    }
+
+    #[test]
+    fn colors_are_kept_by_piece_name() {
+        let table: toml::Table = "[colors]\nT = \"magenta\"\nX = \"#ff8000\"\nS = \"greenish\"\n".parse().unwrap();
+
+        let (mut config, mut errors) = from_table(table);
+        errors.extend(config.validate());
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.colors.get("T").map(String::as_str), Some("magenta"));
+        assert_eq!(config.colors.get("X").map(String::as_str), Some("#ff8000"));
+        assert!(!config.colors.contains_key("S"));
+    }
+}
@@ -0,0 +1,109 @@
+use crossterm::event::KeyCode;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::{generic, config::Config, game_controller::GameController, input_controller::InputController, action::Action, layout::Layout};
+
+const MODE: &str = "dig";
+/// Width of message area, so shorter messages clear longer ones
+const MESSAGE_WIDTH: usize = 20;
+
+/// Picks hole columns of garbage rows, from the top row down
+struct Holes {
+    rng: StdRng,
+    width: i16,
+    /// Chance in percent that a hole moves from the hole of the row above
+    messiness: u8,
+    /// Hole of the bottom row
+    last: Option<i16>,
+}
+
+impl Holes {
+    fn new(config: &Config) -> Holes {
+        Holes {
+            rng: StdRng::seed_from_u64(rand::random()),
+            width: config.display.board_width as i16,
+            messiness: config.dig.messiness,
+            last: None,
+        }
+    }
+
+    /// Hole of the row below the last one
+    fn next(&mut self) -> i16 {
+        let hole = match self.last {
+            Some(last) if self.rng.gen_range(0..100) >= self.messiness => last,
+            // Move to any other column
+            Some(last) => (last + self.rng.gen_range(1..self.width)) % self.width,
+            None => self.rng.gen_range(0..self.width),
+        };
+
+        self.last = Some(hole);
+        hole
+    }
+}
+
+/// Race to clear rows of garbage, with time as the score
+pub fn dig_loop(config: &Config, input_controller: &mut InputController) {
+    generic::clear_terminal();
+    let mut game = GameController::new_at(config, Layout::new(config, MODE), rand::random());
+    let mut holes = Holes::new(config);
+    game.print_message(4, "Dig race   ESC: Stop");
+
+    let rows: Vec<i16> = (0..config.dig.rows).map(|_| holes.next()).collect();
+    game.insert_garbage_rows(&rows);
+
+    let rise_ms = config.dig.rise_secs as u128 * 1000;
+    let mut rows_risen = 0;
+
+    let cleared = loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) || input_controller.action_pressed(Action::Quit) {
+            return;
+        }
+
+        if input_controller.action_pressed(Action::Pause) && !GameController::pause(std::slice::from_mut(&mut game), input_controller) {
+            return;
+        }
+
+        let placed_before = game.get_stats().pieces;
+        game.count_inputs(&input_controller.pressed_actions(0));
+        let actions = input_controller.player_actions(0);
+        if !game.step(&actions) {
+            break false;
+        }
+
+        if game.get_garbage_rows() == 0 {
+            break true;
+        }
+
+        // Rows due to rise are added once the falling Tet is placed, so it isn't pushed into them
+        let rows_due = game.get_play_ms().checked_div(rise_ms).unwrap_or(0);
+        if rows_due > rows_risen && game.get_stats().pieces != placed_before {
+            let rows: Vec<i16> = (rows_risen..rows_due).map(|_| holes.next()).collect();
+            rows_risen = rows_due;
+
+            let topped_out = !game.insert_garbage_rows(&rows) || game.get_tet().is_some_and(|tet| game.is_blocked_out(tet));
+            if let Some(tet) = game.get_tet() {
+                tet.print(false, &game);
+            }
+            if topped_out {
+                break false;
+            }
+        }
+
+        input_controller.end_update();
+    };
+
+    game.finish_animations();
+    if cleared {
+        let centis = game.get_play_ms() / 10;
+        game.print_message(0, &pad(&format!("Cleared in {}:{:02}.{:02}", centis / 6000, centis / 100 % 60, centis % 100)));
+    } else {
+        game.print_message(0, &pad("Game over"));
+    }
+    game.print_message(4, &pad("Press ENTER"));
+    InputController::wait_for_enter();
+}
+
+fn pad(text: &str) -> String {
+    format!("{:<width$}", text, width = MESSAGE_WIDTH)
+}
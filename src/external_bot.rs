@@ -0,0 +1,258 @@
+use std::{collections::VecDeque, io::{BufRead, BufReader, Write}, process::{Child, ChildStdin, Command, Stdio}, sync::mpsc::{self, Receiver, TryRecvError}, thread, time::{Duration, SystemTime}};
+use serde::{Deserialize, Serialize};
+use crate::{action::Action, bot::{self, Autoplayer, Bot}, config::Config, game_controller::GameController, game_state::GameState, piece_set::{self, PieceDef}};
+
+/// Increased when messages change, so bots can tell what to expect
+pub const BOT_PROTOCOL_VERSION: u32 = 3;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Message sent to the bot, as one line of JSON on its stdin
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ToBot {
+    /// Sent after the bot's info, with rules of the game and the pieces that can be dealt
+    Rules {
+        version: u32,
+        board_width: u16,
+        board_height: u16,
+        rotation: String,
+        randomizer: String,
+        /// Name of the piece set
+        piece_set: String,
+        pieces: &'static [PieceDef],
+        big: bool,
+    },
+    /// A new game starts
+    Start,
+    /// Asks for a move for the falling piece, with an id the reply must repeat
+    Suggest { id: u32, state: GameState },
+    /// Game ended
+    GameOver,
+    /// Bot should exit
+    Quit,
+}
+
+/// Message from the bot, as one line of JSON on its stdout
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FromBot {
+    /// First message from the bot
+    Info {
+        name: String,
+        #[serde(default)]
+        author: String,
+    },
+    /// Reply to rules
+    Ready,
+    /// Board cells as (x, y) where the falling piece, or the held piece if `hold`, should land
+    Placement {
+        id: u32,
+        #[serde(default)]
+        hold: bool,
+        cells: Vec<(i16, i16)>,
+    },
+    /// Actions like "move_left" or "hard_drop" to do in order
+    Actions { id: u32, actions: Vec<String> },
+}
+
+impl FromBot {
+    /// Id of the suggest message this replies to, if it's a reply
+    fn get_id(&self) -> Option<u32> {
+        match self {
+            FromBot::Placement { id, .. } | FromBot::Actions { id, .. } => Some(*id),
+            FromBot::Info { .. } | FromBot::Ready => None,
+        }
+    }
+}
+
+/// Bot running as a separate process, talking through stdin and stdout
+///
+/// The bot says info, gets the rules and says ready. Then for each piece it gets a suggest
+/// message with the game state, and replies with a placement or actions with the same id.
+pub struct ExternalBot {
+    name: String,
+    process: Child,
+    stdin: ChildStdin,
+    receiver: Receiver<Result<FromBot, String>>,
+    plan: VecDeque<Action>,
+    /// Pieces placed when last suggestion was asked for, so it's asked once per piece
+    suggest_pieces: Option<u32>,
+    /// Id of the last suggest message, counting up for the whole run so late replies of any earlier piece are ignored
+    suggest_id: u32,
+    waiting: bool,
+    /// Time between each action, or 0 to wait for each reply and do it in one step
+    action_interval_ms: u128,
+    action_time: SystemTime,
+}
+
+impl ExternalBot {
+    /// Start `command` in a shell and exchange info, rules and ready with it
+    pub fn spawn(command: &str, config: &Config, action_interval_ms: u128) -> Result<ExternalBot, String> {
+        let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+        let mut process = Command::new(shell)
+            .args([flag, command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Failed to start bot \"{}\": {}", command, error))?;
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = process.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let message = match line {
+                    Ok(line) => serde_json::from_str(&line).map_err(|error| format!("Invalid bot message: {}", error)),
+                    Err(_) => break,
+                };
+
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+
+            let _ = sender.send(Err(String::from("Bot exited")));
+        });
+
+        let mut bot = ExternalBot {
+            name: String::new(),
+            process,
+            stdin,
+            receiver,
+            plan: VecDeque::new(),
+            suggest_pieces: None,
+            suggest_id: 0,
+            waiting: false,
+            action_interval_ms,
+            action_time: SystemTime::now(),
+        };
+
+        let FromBot::Info { name, author } = bot.receive_wait()? else {
+            return Err(String::from("Bot didn't send info"));
+        };
+        bot.name = if author.is_empty() { name } else { format!("{} by {}", name, author) };
+
+        bot.send(&ToBot::Rules {
+            version: BOT_PROTOCOL_VERSION,
+            board_width: config.display.board_width,
+            board_height: config.display.board_height,
+            rotation: config.gameplay.rotation.clone(),
+            randomizer: config.gameplay.randomizer.clone(),
+            piece_set: config.gameplay.pieces.clone(),
+            pieces: piece_set::get().get_pieces(),
+            big: config.gameplay.big,
+        })?;
+        let FromBot::Ready = bot.receive_wait()? else {
+            return Err(String::from("Bot didn't get ready"));
+        };
+
+        Ok(bot)
+    }
+
+    /// Name and author from the bot's info
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&mut self, message: &ToBot) -> Result<(), String> {
+        let mut line = serde_json::to_string(message).map_err(|error| error.to_string())?;
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).map_err(|_| String::from("Bot exited"))
+    }
+
+    /// Next message from the bot, if any
+    fn receive(&mut self) -> Result<Option<FromBot>, String> {
+        match self.receiver.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(String::from("Bot exited")),
+        }
+    }
+
+    /// Wait for next message, giving up after response timeout
+    fn receive_wait(&mut self) -> Result<FromBot, String> {
+        match self.receiver.recv_timeout(RESPONSE_TIMEOUT) {
+            Ok(message) => message,
+            Err(_) => Err(String::from("Bot didn't respond")),
+        }
+    }
+
+    /// Actions for a reply to a suggest message
+    fn get_plan(message: FromBot, game: &GameController) -> Result<VecDeque<Action>, String> {
+        match message {
+            FromBot::Placement { hold, cells, .. } => Bot::plan_placement(game, hold, &cells)
+                .ok_or(format!("Bot placement {:?} can't be reached", cells)),
+            FromBot::Actions { actions, .. } => actions.iter()
+                .map(|name| match Action::by_config_name(name) {
+                    Some(Action::Pause | Action::Quit) | None => Err(format!("Bot sent unknown action \"{}\"", name)),
+                    Some(action) => Ok(action),
+                })
+                .collect(),
+            FromBot::Info { .. } | FromBot::Ready => Err(String::from("Bot didn't reply with a move")),
+        }
+    }
+}
+
+impl Autoplayer for ExternalBot {
+    fn new_game(&mut self) -> Result<(), String> {
+        self.plan.clear();
+        self.suggest_pieces = None;
+        self.waiting = false;
+        self.send(&ToBot::Start)
+    }
+
+    fn next_actions(&mut self, game: &GameController) -> Result<Vec<Action>, String> {
+        let pieces = game.get_stats().pieces;
+        if game.get_tet().is_some() && self.suggest_pieces != Some(pieces) {
+            self.suggest_id = self.suggest_id.wrapping_add(1);
+            self.send(&ToBot::Suggest { id: self.suggest_id, state: game.get_state() })?;
+            self.suggest_pieces = Some(pieces);
+            self.waiting = true;
+            self.plan.clear();
+        }
+
+        if self.waiting {
+            let reply = loop {
+                let reply = match self.action_interval_ms {
+                    0 => self.receive_wait()?,
+                    _ => match self.receive()? {
+                        Some(reply) => reply,
+                        None => return Ok(Vec::new()),
+                    },
+                };
+
+                // With actions spread over time, a reply for the previous piece can arrive after the next piece spawned
+                match reply.get_id() {
+                    Some(id) if id != self.suggest_id => continue,
+                    _ => break reply,
+                }
+            };
+            self.waiting = false;
+            self.plan = ExternalBot::get_plan(reply, game)?;
+        }
+
+        Ok(bot::take_actions(&mut self.plan, self.action_interval_ms, &mut self.action_time))
+    }
+
+    fn game_over(&mut self) -> Result<(), String> {
+        self.send(&ToBot::GameOver)
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.send(&ToBot::Quit);
+        // Give the bot a moment to exit on its own
+        let start = SystemTime::now();
+        while start.elapsed().unwrap() < Duration::from_millis(500) {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
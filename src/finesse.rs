@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::{game_controller::GameController, tet::Tet};
+
+/// Input counted for finesse, where holding a move key until the wall counts as one input
+#[derive(Clone, Copy, PartialEq)]
+pub enum FinesseInput {
+    Left,
+    Right,
+    DasLeft,
+    DasRight,
+    RotateCW,
+    RotateCCW,
+}
+
+impl FinesseInput {
+    /// Tried in this order, so taps are preferred over holding when both are as short
+    const ALL: [FinesseInput; 6] = [
+        FinesseInput::Left,
+        FinesseInput::Right,
+        FinesseInput::RotateCW,
+        FinesseInput::RotateCCW,
+        FinesseInput::DasLeft,
+        FinesseInput::DasRight,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FinesseInput::Left => "L",
+            FinesseInput::Right => "R",
+            FinesseInput::DasLeft => "DAS-L",
+            FinesseInput::DasRight => "DAS-R",
+            FinesseInput::RotateCW => "CW",
+            FinesseInput::RotateCCW => "CCW",
+        }
+    }
+
+    /// `tet` after this input, or `None` if it can't move
+    fn apply(&self, tet: &Tet, game: &GameController) -> Option<Tet> {
+        let direction = match self {
+            FinesseInput::RotateCW => return tet.rotated(true, game),
+            FinesseInput::RotateCCW => return tet.rotated(false, game),
+            FinesseInput::Left | FinesseInput::DasLeft => -1,
+            FinesseInput::Right | FinesseInput::DasRight => 1,
+        };
+        let das = matches!(self, FinesseInput::DasLeft | FinesseInput::DasRight);
+
+        let mut moved = tet.clone();
+        let step = direction * tet.get_step();
+        while !game.collision_check(&moved.points_pos(), step, 0) {
+            moved.pos.x += step;
+            if !das {
+                break;
+            }
+        }
+
+        if moved.pos.x == tet.pos.x { None } else { Some(moved) }
+    }
+}
+
+/// Fewest inputs for each placement of a Tet, before hard dropping it
+///
+/// Only placements reached without soft drop are included, so tucks and spins have no entry
+pub struct FinesseTable {
+    /// Inputs by sorted cells where the Tet lands
+    inputs: HashMap<Vec<(i16, i16)>, Vec<FinesseInput>>,
+}
+
+impl FinesseTable {
+    /// Search every placement reachable from `tet` on the current board of `game`
+    pub fn new(tet: &Tet, game: &GameController) -> FinesseTable {
+        let mut inputs = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([(tet.clone(), Vec::new())]);
+        visited.insert((tet.pos.x, tet.pos.y, tet.get_flips()));
+
+        // Breadth first, so the first way found to each placement is the shortest
+        while let Some((tet, path)) = queue.pop_front() {
+            inputs.entry(FinesseTable::landing_cells(&tet, game)).or_insert_with(|| path.clone());
+
+            for input in FinesseInput::ALL {
+                let Some(next) = input.apply(&tet, game) else {
+                    continue;
+                };
+
+                if visited.insert((next.pos.x, next.pos.y, next.get_flips())) {
+                    let mut next_path = path.clone();
+                    next_path.push(input);
+                    queue.push_back((next, next_path));
+                }
+            }
+        }
+
+        FinesseTable { inputs }
+    }
+
+    /// Fewest inputs to land the Tet at `cells`, in any order
+    pub fn get_inputs(&self, cells: &[(i16, i16)]) -> Option<&[FinesseInput]> {
+        let mut cells = cells.to_vec();
+        cells.sort();
+        self.inputs.get(&cells).map(|inputs| inputs.as_slice())
+    }
+
+    fn landing_cells(tet: &Tet, game: &GameController) -> Vec<(i16, i16)> {
+        let distance = tet.drop_distance(game);
+        let mut cells: Vec<(i16, i16)> = tet.points_pos().iter().map(|point| (point.x, point.y + distance)).collect();
+        cells.sort();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, generic, tet::TetKind};
+
+    fn table_of(name: &str) -> (FinesseTable, Tet, GameController) {
+        generic::set_headless(true);
+        let game = GameController::new(&Config::default());
+        let tet = Tet::new(TetKind::by_name(name).unwrap(), &game);
+        (FinesseTable::new(&tet, &game), tet, game)
+    }
+
+    #[test]
+    fn o_to_left_wall_is_das_left() {
+        let (table, _, _) = table_of("O");
+
+        let inputs = table.get_inputs(&[(0, 18), (1, 18), (0, 19), (1, 19)]);
+        assert!(inputs == Some(&[FinesseInput::DasLeft][..]));
+    }
+
+    #[test]
+    fn flat_t_one_column_right_is_right() {
+        let (table, mut t, game) = table_of("T");
+        t.pos.x += 1;
+
+        let inputs = table.get_inputs(&FinesseTable::landing_cells(&t, &game));
+        assert!(inputs == Some(&[FinesseInput::Right][..]));
+    }
+}
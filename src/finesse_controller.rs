@@ -0,0 +1,105 @@
+use crossterm::event::KeyCode;
+use crate::{generic, config::Config, game_controller::GameController, input_controller::InputController, action::Action, layout::Layout, tet::Tet, finesse::FinesseTable};
+
+const MODE: &str = "finesse";
+/// Width of message area, so shorter messages clear longer ones
+const MESSAGE_WIDTH: usize = 20;
+
+/// Inputs counted for the falling Tet
+struct PieceFinesse {
+    table: FinesseTable,
+    inputs: u32,
+    /// Pieces placed and hold used when piece spawned, to tell when a new piece spawns
+    spawn: (u32, bool),
+}
+
+/// Marathon without scoring, counting extra moves and rotations of each piece as finesse faults
+pub fn finesse_loop(config: &Config, input_controller: &mut InputController) {
+    generic::clear_terminal();
+    let mut game = GameController::new_at(config, Layout::new(config, MODE), rand::random());
+    let mut faults = 0;
+    game.print_message(4, "Finesse   ESC: Stop");
+
+    // Spawn first Tet, so inputs can be counted from the start
+    game.step(&[]);
+    let mut piece = new_piece(&game);
+
+    loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) || input_controller.action_pressed(Action::Quit) {
+            return;
+        }
+
+        if input_controller.action_pressed(Action::Pause) && !GameController::pause(std::slice::from_mut(&mut game), input_controller) {
+            return;
+        }
+
+        // Holding a move key counts as one input, since it's only pressed once
+        piece.inputs += input_controller.pressed_actions(0).iter()
+            .filter(|action| matches!(action, Action::MoveLeft | Action::MoveRight | Action::RotateCW | Action::RotateCCW))
+            .count() as u32;
+
+        let placed_before = game.get_stats().pieces;
+        game.count_inputs(&input_controller.pressed_actions(0));
+        let actions = input_controller.player_actions(0);
+        if !game.step(&actions) {
+            break;
+        }
+
+        if game.get_stats().pieces != placed_before {
+            if let Some(placed) = game.get_last_placed() {
+                faults += judge(&game, &piece, placed);
+            }
+            game.print_message(0, &pad(&format!("Faults: {} in {}", faults, game.get_stats().pieces)));
+        }
+
+        if spawn_of(&game) != piece.spawn {
+            piece = new_piece(&game);
+        }
+
+        input_controller.end_update();
+    }
+
+    game.print_message(3, &pad("Game over"));
+    game.print_message(4, &pad("Press ENTER"));
+    InputController::wait_for_enter();
+}
+
+fn spawn_of(game: &GameController) -> (u32, bool) {
+    (game.get_stats().pieces, game.is_hold_used())
+}
+
+/// Start counting inputs of the falling Tet, from where it spawned
+fn new_piece(game: &GameController) -> PieceFinesse {
+    let kind = game.get_tet().map(|tet| tet.kind).unwrap_or(game.get_queue()[0]);
+
+    PieceFinesse {
+        table: FinesseTable::new(&Tet::new(kind, game), game),
+        inputs: 0,
+        spawn: spawn_of(game),
+    }
+}
+
+/// Show inputs used for `placed` against the fewest possible, and return faults
+fn judge(game: &GameController, piece: &PieceFinesse, placed: &Tet) -> u32 {
+    let cells: Vec<(i16, i16)> = placed.points_pos().iter().map(|point| (point.x, point.y)).collect();
+    let Some(best) = piece.table.get_inputs(&cells) else {
+        game.print_message(1, &pad("Tuck or spin"));
+        game.print_message(2, &pad(""));
+        return 0;
+    };
+
+    let labels: Vec<&str> = best.iter().map(|input| input.label()).collect();
+    let faults = piece.inputs.saturating_sub(best.len() as u32);
+    let result = if faults == 0 { String::from("OK") } else { format!("+{} fault", faults) };
+    game.print_message(1, &pad(&format!("Inputs: {}/{} {}", piece.inputs, best.len(), result)));
+    let best_text = if labels.is_empty() { String::from("drop") } else { labels.join(" ") };
+    game.print_message(2, &pad(&format!("Best: {}", best_text)));
+
+    faults
+}
+
+fn pad(text: &str) -> String {
+    format!("{:width$.width$}", text, width = MESSAGE_WIDTH)
+}
@@ -0,0 +1,360 @@
+use std::{fs::OpenOptions, io::Write};
+use crate::game_state::{GameState, PieceState, EMPTY_CELL, GARBAGE_CELL};
+
+/// File fumen codes are saved to
+pub const FILE: &str = "fumen.txt";
+const PREFIX: &str = "v115@";
+const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const FIELD_WIDTH: usize = 10;
+/// Rows above the garbage row, which is below the floor and isn't used
+const FIELD_TOP: usize = 23;
+const FIELD_BLOCKS: usize = FIELD_WIDTH * (FIELD_TOP + 1);
+/// Fumen piece numbers of the standard pieces, where 0 is empty and 8 is garbage
+const PIECE_NUMBERS: [(&str, u32); 7] = [
+    ("I", 1),
+    ("L", 2),
+    ("O", 3),
+    ("Z", 4),
+    ("T", 5),
+    ("J", 6),
+    ("S", 7),
+];
+const GARBAGE_NUMBER: u32 = 8;
+/// Flags of an action, with only colorize set, as tools expect on the first page
+const ACTION_FLAGS: u32 = 0b00100;
+
+/// Fumen rotation numbers, in order of a clockwise turn from spawn
+#[derive(Clone, Copy, PartialEq)]
+enum Rotation {
+    Spawn = 2,
+    Right = 1,
+    Reverse = 0,
+    Left = 3,
+}
+
+impl Rotation {
+    const ALL: [Rotation; 4] = [Rotation::Spawn, Rotation::Right, Rotation::Reverse, Rotation::Left];
+
+    fn by_number(number: u32) -> Rotation {
+        *Rotation::ALL.iter().find(|rotation| **rotation as u32 == number).unwrap()
+    }
+}
+
+/// Decode the first page of a fumen code into a board `width` by `height` and the piece of the page
+///
+/// Codes can be given with or without a fumen URL in front
+pub fn decode(code: &str, width: u16, height: u16) -> Result<GameState, String> {
+    check_width(width)?;
+
+    let data = match code.find(PREFIX) {
+        Some(index) => &code[index + PREFIX.len()..],
+        None => return Err(String::from("Not a fumen code, which starts with v115@")),
+    };
+    let mut reader = Reader { values: Vec::new(), index: 0 };
+    for c in data.chars().filter(|c| *c != '?') {
+        let value = CHARS.iter().position(|char| *char as char == c).ok_or(format!("Invalid fumen character \"{}\"", c))?;
+        reader.values.push(value as u32);
+    }
+
+    // Blocks are run-length encoded as changes from the previous page, which is empty for the first page
+    let mut field = Vec::with_capacity(FIELD_BLOCKS);
+    while field.len() < FIELD_BLOCKS {
+        let value = reader.read(2)?;
+        let (number, count) = ((value / FIELD_BLOCKS as u32).wrapping_sub(8), value as usize % FIELD_BLOCKS + 1);
+        if number > GARBAGE_NUMBER || field.len() + count > FIELD_BLOCKS {
+            return Err(String::from("Invalid fumen board"));
+        }
+        field.extend(std::iter::repeat_n(number, count));
+
+        // Unchanged board is followed by how many more pages are unchanged
+        if count == FIELD_BLOCKS && number == 0 {
+            reader.read(1)?;
+        }
+    }
+
+    let mut action = reader.read(3)?;
+    let piece_number = action % 8;
+    action /= 8;
+    let rotation = Rotation::by_number(action % 4);
+    action /= 4;
+    let position = action as usize % FIELD_BLOCKS;
+
+    let mut rows = vec![vec![EMPTY_CELL; FIELD_WIDTH]; height as usize];
+    for (index, number) in field.iter().enumerate().take(FIELD_WIDTH * FIELD_TOP) {
+        if *number == 0 {
+            continue;
+        }
+
+        let field_y = (FIELD_TOP - 1 - index / FIELD_WIDTH) as i16;
+        let y = to_board_y(field_y, height);
+        if y < 0 {
+            return Err(format!("Fumen board is too high for a board {} rows high", height));
+        }
+        rows[y as usize][index % FIELD_WIDTH] = match kind_of(*number) {
+            Some(kind) => kind.chars().next().unwrap(),
+            None => GARBAGE_CELL,
+        };
+    }
+
+    let piece = kind_of(piece_number).map(|kind| {
+        let x = (position % FIELD_WIDTH) as i16;
+        let y = (FIELD_TOP - 1 - position / FIELD_WIDTH) as i16;
+        let (offset_x, offset_y) = position_offset(kind, rotation);
+        let (center_x, center_y) = (x - offset_x, y - offset_y);
+
+        PieceState {
+            kind: String::from(kind),
+            cells: blocks(kind, rotation).iter()
+                .map(|(block_x, block_y)| (center_x + block_x, to_board_y(center_y + block_y, height)))
+                .collect(),
+        }
+    });
+
+    Ok(GameState {
+        board: rows.iter().map(|row| row.iter().collect()).collect(),
+        piece,
+        ..Default::default()
+    })
+}
+
+/// Encode board and falling piece of `state` as a one page fumen code
+pub fn encode(state: &GameState) -> Result<String, String> {
+    let height = state.board.len() as u16;
+    let width = state.board.first().map(|row| row.chars().count()).unwrap_or(0) as u16;
+    check_width(width)?;
+
+    let mut field = vec![0; FIELD_BLOCKS];
+    for (y, row) in state.board.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            if cell == EMPTY_CELL {
+                continue;
+            }
+
+            let field_y = to_board_y(y as i16, height);
+            if field_y >= FIELD_TOP as i16 {
+                return Err(format!("Fumen boards are at most {} rows high", FIELD_TOP));
+            }
+            field[(FIELD_TOP - 1 - field_y as usize) * FIELD_WIDTH + x] = number_of(&cell.to_string()).unwrap_or(GARBAGE_NUMBER);
+        }
+    }
+
+    let mut data = String::new();
+    let mut index = 0;
+    while index < FIELD_BLOCKS {
+        let count = field[index..].iter().take_while(|number| **number == field[index]).count();
+        write(&mut data, (field[index] + 8) * FIELD_BLOCKS as u32 + count as u32 - 1, 2);
+        if count == FIELD_BLOCKS && field[index] == 0 {
+            write(&mut data, 0, 1);
+        }
+        index += count;
+    }
+
+    let (piece_number, rotation, position) = match &state.piece {
+        Some(piece) => piece_action(piece, height)?,
+        None => (0, Rotation::Reverse, 0),
+    };
+    let action = ((ACTION_FLAGS * FIELD_BLOCKS as u32 + position) * 4 + rotation as u32) * 8 + piece_number;
+    write(&mut data, action, 3);
+
+    // Long codes are split by question marks, like other fumen tools do
+    let chunks: Vec<&str> = std::iter::once(&data[..data.len().min(42)])
+        .chain(data.as_bytes().get(42..).unwrap_or_default().chunks(47).map(|chunk| std::str::from_utf8(chunk).unwrap()))
+        .collect();
+    Ok(format!("{}{}", PREFIX, chunks.join("?")))
+}
+
+/// Add `code` as a line to the fumen file
+pub fn save(code: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new().create(true).append(true).open(FILE)
+        .map_err(|_| format!("Failed to open fumen file {}", FILE))?;
+    writeln!(file, "{}", code).map_err(|_| format!("Failed to write to fumen file {}", FILE))
+}
+
+struct Reader {
+    values: Vec<u32>,
+    index: usize,
+}
+
+impl Reader {
+    /// Read a number written as `length` characters, least significant first
+    fn read(&mut self, length: usize) -> Result<u32, String> {
+        let values = self.values.get(self.index..self.index + length).ok_or(String::from("Fumen code ended early"))?;
+        self.index += length;
+        Ok(values.iter().rev().fold(0, |number, value| number * 64 + value))
+    }
+}
+
+fn write(data: &mut String, mut number: u32, length: usize) {
+    for _ in 0..length {
+        data.push(CHARS[number as usize % 64] as char);
+        number /= 64;
+    }
+}
+
+fn check_width(width: u16) -> Result<(), String> {
+    if width as usize != FIELD_WIDTH {
+        return Err(format!("Fumen boards are {} wide, but the board is {} wide", FIELD_WIDTH, width));
+    }
+    Ok(())
+}
+
+fn kind_of(number: u32) -> Option<&'static str> {
+    PIECE_NUMBERS.iter().find(|(_, kind_number)| *kind_number == number).map(|(kind, _)| *kind)
+}
+
+/// Number of piece `kind`, if it's one of the standard pieces
+fn number_of(kind: &str) -> Option<u32> {
+    PIECE_NUMBERS.iter().find(|(piece_kind, _)| *piece_kind == kind).map(|(_, number)| *number)
+}
+
+/// Convert between fumen rows, counted up from the floor, and board rows, counted down from the top
+fn to_board_y(field_y: i16, height: u16) -> i16 {
+    height as i16 - 1 - field_y
+}
+
+/// Blocks of `kind` around its center, with y pointing up
+fn blocks(kind: &str, rotation: Rotation) -> Vec<(i16, i16)> {
+    let spawn: [(i16, i16); 4] = match kind {
+        "I" => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        "T" => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+        "O" => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        "L" => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        "J" => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        "S" => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        "Z" => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+        _ => return Vec::new(),
+    };
+
+    spawn.iter()
+        .map(|(x, y)| match rotation {
+            Rotation::Spawn => (*x, *y),
+            Rotation::Right => (*y, -x),
+            Rotation::Reverse => (-x, -y),
+            Rotation::Left => (-y, *x),
+        })
+        .collect()
+}
+
+/// Offset of the position stored in fumen from the center of the blocks
+fn position_offset(kind: &str, rotation: Rotation) -> (i16, i16) {
+    match (kind, rotation) {
+        ("O", Rotation::Left) => (-1, 1),
+        ("O", Rotation::Reverse) => (-1, 0),
+        ("O", Rotation::Spawn) => (0, 1),
+        ("I", Rotation::Reverse) => (-1, 0),
+        ("I", Rotation::Left) => (0, 1),
+        ("S", Rotation::Spawn) => (0, 1),
+        ("S", Rotation::Right) => (1, 0),
+        ("Z", Rotation::Spawn) => (0, 1),
+        ("Z", Rotation::Left) => (-1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// Piece number, rotation and position of `piece`, found by matching its cells to each rotation
+fn piece_action(piece: &PieceState, height: u16) -> Result<(u32, Rotation, u32), String> {
+    let number = number_of(&piece.kind).ok_or(format!("Fumen only has the standard pieces, not \"{}\"", piece.kind))?;
+    let kind = piece.kind.as_str();
+    let mut cells: Vec<(i16, i16)> = piece.cells.iter().map(|(x, y)| (*x, to_board_y(*y, height))).collect();
+    cells.sort();
+
+    for rotation in Rotation::ALL {
+        let mut rotated = blocks(kind, rotation);
+        rotated.sort();
+        let (center_x, center_y) = (cells[0].0 - rotated[0].0, cells[0].1 - rotated[0].1);
+        if !rotated.iter().map(|(x, y)| (center_x + x, center_y + y)).eq(cells.iter().copied()) {
+            continue;
+        }
+
+        let (offset_x, offset_y) = position_offset(kind, rotation);
+        let (x, y) = (center_x + offset_x, center_y + offset_y);
+        if !(0..FIELD_TOP as i16).contains(&y) {
+            return Err(String::from("Piece is outside of the fumen board"));
+        }
+        return Ok((number, rotation, (FIELD_TOP as u32 - 1 - y as u32) * FIELD_WIDTH as u32 + x as u32));
+    }
+
+    Err(String::from("Piece doesn't match any rotation"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Codes as tetris-fumen encodes these pages, which stores vertical S and Z pieces off their center
+
+    const EMPTY: &str = "v115@vhAAgH";
+    /// S turned right with its center at the bottom left, at x 0 and y 1 counted up from the floor
+    const S_RIGHT: &str = "v115@vhAvJJ";
+    /// S turned left covering the same cells, with its center at x 1 and y 1
+    const S_LEFT: &str = "v115@vhA/JJ";
+    /// Z turned left with its center at x 9 and y 1
+    const Z_LEFT: &str = "v115@vhAcNJ";
+    /// Z turned right covering the same cells, with its center at x 8 and y 1
+    const Z_RIGHT: &str = "v115@vhAMNJ";
+    /// Two rows of garbage with a hole on the right, and an I turned left in the hole
+    const GARBAGE_I: &str = "v115@RhI8AeI8Ke5IJ";
+    /// Same board and cells, with the I turned right
+    const GARBAGE_I_RIGHT: &str = "v115@RhI8AeI8KepIJ";
+
+    fn piece_cells(state: &GameState) -> Vec<(i16, i16)> {
+        let mut cells = state.piece.as_ref().unwrap().cells.clone();
+        cells.sort();
+        cells
+    }
+
+    fn state(board: Vec<&str>, kind: &str, cells: Vec<(i16, i16)>) -> GameState {
+        GameState {
+            board: board.iter().map(|row| String::from(*row)).collect(),
+            piece: Some(PieceState { kind: String::from(kind), cells }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decodes_empty_page() {
+        let state = decode(EMPTY, 10, 20).unwrap();
+
+        assert!(state.piece.is_none());
+        assert!(state.board.iter().all(|row| row == ".........."));
+    }
+
+    #[test]
+    fn decodes_vertical_s_and_z() {
+        for code in [S_RIGHT, S_LEFT] {
+            let s = decode(code, 10, 20).unwrap();
+            assert_eq!(s.piece.as_ref().unwrap().kind, "S");
+            assert_eq!(piece_cells(&s), vec![(0, 17), (0, 18), (1, 18), (1, 19)]);
+        }
+
+        for code in [Z_LEFT, Z_RIGHT] {
+            let z = decode(code, 10, 20).unwrap();
+            assert_eq!(z.piece.as_ref().unwrap().kind, "Z");
+            assert_eq!(piece_cells(&z), vec![(8, 18), (8, 19), (9, 17), (9, 18)]);
+        }
+    }
+
+    #[test]
+    fn decodes_board_with_piece() {
+        for code in [GARBAGE_I, GARBAGE_I_RIGHT] {
+            let state = decode(&format!("https://harddrop.com/fumen/?{}", code), 10, 20).unwrap();
+
+            assert!(state.board[..18].iter().all(|row| row == ".........."));
+            assert_eq!(state.board[18], "GGGGGGGGG.");
+            assert_eq!(state.board[19], "GGGGGGGGG.");
+            assert_eq!(piece_cells(&state), vec![(9, 16), (9, 17), (9, 18), (9, 19)]);
+        }
+    }
+
+    #[test]
+    fn encodes_like_tetris_fumen() {
+        let empty = vec![".........."; 20];
+        let mut garbage = vec![".........."; 18];
+        garbage.extend(["GGGGGGGGG.", "GGGGGGGGG."]);
+
+        // Vertical pieces match both their right and left rotation, and are written turned right
+        assert_eq!(encode(&state(empty.clone(), "S", vec![(0, 18), (0, 17), (1, 18), (1, 19)])).unwrap(), S_RIGHT);
+        assert_eq!(encode(&state(empty, "Z", vec![(9, 18), (9, 17), (8, 18), (8, 19)])).unwrap(), Z_RIGHT);
+        assert_eq!(encode(&state(garbage, "I", vec![(9, 16), (9, 17), (9, 18), (9, 19)])).unwrap(), GARBAGE_I_RIGHT);
+    }
+}
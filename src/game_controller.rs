@@ -1,80 +1,226 @@
-use std::time::SystemTime;
+use std::{collections::VecDeque, time::{Duration, SystemTime}};
 use crossterm::{event::KeyCode, style::Color};
-use crate::{point::Point, board::Board, generic, input_controller::InputController, tet::Tet, menu_controller, highscore_controller};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::{point::Point, grid::{Block, Grid}, board::{Board, GARBAGE_COLOR}, game_state::{GameState, PieceState, EMPTY_CELL, GARBAGE_CELL}, generic, input_controller::InputController, tet::{Fall, Tet, TetKind}, menu_controller, highscore_controller, stats_controller::{self, Stats}, config::Config, action::Action, animation::{Animator, AnimationKind}, layout::{Layout, PanelKind}, randomizer::Randomizer, publisher::Publisher, fumen, piece_set};
 
-const LEVEL_SCALE: u8 = 5;
-const LEVEL_AT_SCORE: u16 = 150;
-const INITIAL_FALL_MS: u16 = 750;
+const MODE: &str = "marathon";
+/// Garbage lines sent per cleared row count
+const ATTACK: [u16; 5] = [0, 0, 1, 2, 4];
 
 pub struct GameController {
-    pub occupied: Vec<Point>,
+    pub config: Config,
+    pub occupied: Grid,
     time: SystemTime,
     score: u32,
     pub board: Board,
     level: u32,
     fall_ms: u16,
+    start_time: SystemTime,
+    stats: Stats,
+    hold: Option<TetKind>,
+    /// Hold was used for current Tet
+    hold_used: bool,
+    animator: Animator,
+    layout: Layout,
+    /// Upcoming Tets, at least one unless the sequence is fixed
+    queue: VecDeque<TetKind>,
+    randomizer: Randomizer,
+    /// Queue holds all remaining Tets, so none are added from the randomizer
+    sequence_fixed: bool,
+    /// Falling Tet, taken from queue on first step
+    tet: Option<Tet>,
+    /// Tet placed last, where it landed
+    last_placed: Option<Tet>,
+    /// Seconds shown by timer panel
+    timer_secs: u64,
+    /// Picks hole column of garbage lines
+    garbage_rng: StdRng,
+    /// Garbage lines to add when next Tet is placed without clearing rows
+    incoming_garbage: u16,
+    /// Garbage lines sent to opponent, not yet taken by `take_sent_garbage`
+    sent_garbage: u16,
 }
 
 impl GameController {
-    pub fn new() -> GameController {
+    pub fn new(config: &Config) -> GameController {
         generic::clear_terminal();
+        GameController::new_at(config, Layout::new(config, MODE), rand::random())
+    }
+
+    /// Game drawn at `layout`, getting the same Tets as other games with the same `seed`
+    pub fn new_at(config: &Config, layout: Layout, seed: u64) -> GameController {
+        let (callout_x, callout_y) = layout.get_message(0);
+        let mut randomizer = Randomizer::new(config, seed);
+        let queue = (0..config.gameplay.preview_count.max(1)).map(|_| randomizer.next()).collect();
 
-        let this = GameController {
-            occupied: Vec::new(),
+        let mut this = GameController {
+            config: config.clone(),
+            // As many hidden rows as visible ones, so pieces pushed up by garbage are rarely lost
+            occupied: Grid::new(config.display.board_width, config.display.board_height, config.display.board_height),
             time: SystemTime::now(),
             score: 0,
-            board: Board::new(),
-            level: 1,
-            fall_ms: INITIAL_FALL_MS,
+            board: Board::new(config, layout.get_board_x(), layout.get_board_y()),
+            level: config.gameplay.start_level as u32,
+            fall_ms: config.gameplay.initial_fall_ms,
+            start_time: SystemTime::now(),
+            stats: Stats::new_game(),
+            hold: None,
+            hold_used: false,
+            animator: Animator::new(callout_x, callout_y),
+            layout,
+            queue,
+            randomizer,
+            sequence_fixed: false,
+            tet: None,
+            last_placed: None,
+            timer_secs: 0,
+            garbage_rng: StdRng::seed_from_u64(seed),
+            incoming_garbage: 0,
+            sent_garbage: 0,
         };
+        this.set_fall_ms();
 
         this.board.draw_frame();
         this.board.clear_board();
-        this.print_score();
-        this.print_level();
+        for kind in [PanelKind::Hold, PanelKind::Next, PanelKind::Score, PanelKind::Level, PanelKind::Lines, PanelKind::Timer, PanelKind::Stats, PanelKind::Garbage, PanelKind::GarbageLeft] {
+            this.print_panel(kind);
+        }
 
         this
     }
 
-    pub fn game_loop(&mut self, input_controller: &mut InputController) {
-        let mut tet = Tet::new_random();
-
+    /// Play until topped out or quit, sending game states to spectators of `publisher`
+    pub fn game_loop(&mut self, input_controller: &mut InputController, mut publisher: Option<&mut Publisher>) {
         loop {
             input_controller.update();
 
-            if input_controller.key_pressed(KeyCode::Esc) {
-                self.game_over(input_controller);
-                return;
+            if input_controller.action_pressed(Action::Quit) {
+                break;
             }
 
-            // Auto fall
-            if self.should_autofall() {
-                if !tet.move_down(self) {
-                    break;
-                }
+            if input_controller.action_pressed(Action::Pause) && !GameController::pause(std::slice::from_mut(self), input_controller) {
+                break;
             }
 
-            if input_controller.key_hold(KeyCode::Left) {
-                tet.translate(-1, 0, self);
+            self.count_inputs(&input_controller.pressed_actions(0));
+            let actions = input_controller.player_actions(0);
+            if !self.step(&actions) {
+                break;
             }
-            if input_controller.key_hold(KeyCode::Right) {
-                tet.translate(1, 0, self);
+
+            if let Some(publisher) = &mut publisher {
+                publisher.update(self);
             }
-            if input_controller.key_hold(KeyCode::Down) {
-                if !tet.move_down(self) {
-                    break;
+
+            input_controller.end_update();
+        }
+
+        if let Some(publisher) = publisher {
+            publisher.game_over(self);
+        }
+
+        self.game_over(input_controller);
+    }
+
+    /// Run one cycle of the game, handling `actions` in order
+    ///
+    /// Returns false when topped out, or out of Tets of a fixed sequence
+    pub fn step(&mut self, actions: &[Action]) -> bool {
+        let tet = match self.tet.take() {
+            Some(tet) => Some(tet),
+            // Next Tet waits until cleared rows have flashed and collapsed, so it never moves among blocks that aren't drawn yet
+            None if self.animator.is_flashing_rows() => None,
+            None => {
+                if self.is_out_of_tets() {
+                    return false;
+                }
+                let tet = self.next_tet();
+                if self.is_blocked_out(&tet) {
+                    return false;
                 }
-                self.reset_time();
+                tet.print(false, self);
+                Some(tet)
+            },
+        };
+
+        if let Some(mut tet) = tet {
+            match self.step_tet(&mut tet, actions) {
+                Fall::Moved => self.tet = Some(tet),
+                Fall::Placed => (),
+                Fall::ToppedOut => return false,
+            }
+        }
+
+        self.update_timer();
+
+        if self.animator.update(&self.board) {
+            self.print_occupied();
+            if let Some(tet) = &self.tet {
+                tet.print(false, self);
             }
+        }
+
+        // Pieces of a fixed sequence end the game when the last one is placed, not when the next one is due
+        !(self.tet.is_none() && self.is_out_of_tets())
+    }
 
-            if input_controller.key_pressed(KeyCode::Up) {
-                tet.rotate(self);
+    /// Fall and handle `actions` until `tet` is placed
+    fn step_tet(&mut self, tet: &mut Tet, actions: &[Action]) -> Fall {
+        // Auto fall
+        if self.should_autofall() {
+            let fall = tet.move_down(self);
+            if fall != Fall::Moved {
+                return fall;
             }
+        }
 
-            input_controller.end_update();
+        for action in actions {
+            match action {
+                Action::MoveLeft => {
+                    tet.translate(-tet.get_step(), 0, self);
+                },
+                Action::MoveRight => {
+                    tet.translate(tet.get_step(), 0, self);
+                },
+                Action::SoftDrop => {
+                    let fall = tet.move_down(self);
+                    self.reset_time();
+                    if fall != Fall::Moved {
+                        return fall;
+                    }
+                },
+                Action::HardDrop => {
+                    self.reset_time();
+                    return tet.hard_drop(self);
+                },
+                Action::RotateCW => tet.rotate(true, self),
+                Action::RotateCCW => tet.rotate(false, self),
+                Action::Hold => {
+                    if !self.hold(tet) {
+                        return Fall::ToppedOut;
+                    }
+                },
+                // Handled by caller
+                Action::Pause | Action::Quit => (),
+            }
         }
 
-        self.game_over(input_controller);
+        Fall::Moved
+    }
+
+    /// Count `pressed` actions as inputs of stats
+    ///
+    /// Only key presses count, since repeats of held keys and actions of bots would make inputs per piece meaningless
+    pub fn count_inputs(&mut self, pressed: &[Action]) {
+        let inputs = pressed.iter().filter(|action| !matches!(action, Action::Pause | Action::Quit)).count();
+        self.stats.add_inputs(inputs as u32);
+    }
+
+    /// Finish running animations, so the board is drawn as it is when the game ends
+    pub fn finish_animations(&mut self) {
+        if self.animator.finish_all(&self.board) {
+            self.print_occupied();
+        }
     }
 
     pub fn reset_time(&mut self) {
@@ -92,10 +238,303 @@ impl GameController {
         should_fall
     }
 
-    pub fn place_tet(&mut self) {
+    /// Swap `tet` with held Tet, or next Tet if none is held
+    ///
+    /// Only allowed once per placed Tet. Returns false if the swapped in Tet is blocked out
+    fn hold(&mut self, tet: &mut Tet) -> bool {
+        if self.hold_used || (self.hold.is_none() && self.queue.is_empty()) {
+            return true;
+        }
+
+        tet.print(true, self);
+        *tet = match self.hold.replace(tet.kind) {
+            Some(kind) => Tet::new(kind, self),
+            None => self.next_tet(),
+        };
+        tet.print(false, self);
+
+        self.hold_used = true;
+        self.print_panel(PanelKind::Hold);
+        !self.is_blocked_out(tet)
+    }
+
+    /// Block out: `tet` spawned overlapping blocks, which ends the game
+    pub fn is_blocked_out(&self, tet: &Tet) -> bool {
+        self.collision_check(&tet.points_pos(), 0, 0)
+    }
+
+    /// Take next Tet from queue, and add a Tet from the randomizer to the queue
+    ///
+    /// Must not be called when out of Tets
+    pub fn next_tet(&mut self) -> Tet {
+        let kind = self.queue.pop_front().unwrap();
+        if !self.sequence_fixed {
+            self.queue.push_back(self.randomizer.next());
+        }
+        self.print_panel(PanelKind::Next);
+
+        Tet::new(kind, self)
+    }
+
+    /// Pause `games` until unpaused
+    ///
+    /// Returns false if quit while paused
+    pub fn pause(games: &mut [GameController], input_controller: &mut InputController) -> bool {
+        let pause_time = SystemTime::now();
+        for game in games.iter() {
+            game.print_message(2, "Paused");
+            game.print_message(3, "F: Save fumen");
+        }
+
+        let resume = loop {
+            input_controller.update();
+
+            if input_controller.key_pressed(KeyCode::Char('f')) {
+                for game in games.iter() {
+                    let saved = game.get_fumen().and_then(|code| fumen::save(&code));
+                    game.print_message(3, &format!("{:20.20}", match saved {
+                        Ok(_) => format!("Saved to {}", fumen::FILE),
+                        Err(error) => error,
+                    }));
+                }
+            }
+
+            if input_controller.action_pressed(Action::Pause) {
+                break true;
+            }
+            if input_controller.action_pressed(Action::Quit) {
+                break false;
+            }
+        };
+
+        for game in games.iter_mut() {
+            game.print_message(2, "      ");
+            game.print_message(3, &" ".repeat(20));
+            game.resume(pause_time.elapsed().unwrap());
+        }
+
+        resume
+    }
+
+    /// Continue after being paused for `paused`, which doesn't count as play time
+    fn resume(&mut self, paused: Duration) {
+        self.start_time += paused;
+        self.reset_time();
+    }
+
+    /// Print `text` at `line` of the message area
+    pub fn print_message(&self, line: u16, text: &str) {
+        if generic::headless() {
+            return;
+        }
+
+        let (message_x, message_y) = self.layout.get_message(line);
+        generic::move_cursor(message_x, message_y);
+        print!("{}", text);
+        generic::move_cursor(0, 0);
+    }
+
+    /// Add garbage lines, which rise from the bottom when a Tet is placed without clearing rows
+    pub fn add_garbage(&mut self, lines: u16) {
+        if lines == 0 {
+            return;
+        }
+
+        self.incoming_garbage += lines;
+        self.print_panel(PanelKind::Garbage);
+    }
+
+    /// Falling Tet, if the game has started
+    pub fn get_tet(&self) -> Option<&Tet> {
+        self.tet.as_ref()
+    }
+
+    /// Replace the board with `cells` as (x, y, kind), where no kind is a garbage block
+    pub fn set_occupied(&mut self, cells: &[(i16, i16, Option<TetKind>)]) {
+        self.occupied.clear();
+        for (x, y, kind) in cells {
+            let block = match kind {
+                Some(kind) => Block { color: self.board.get_color(*kind), kind: Some(*kind) },
+                None => GameController::garbage_block(),
+            };
+            self.occupied.set(*x, *y, Some(block));
+        }
+        self.print_occupied();
+    }
+
+    /// Replace the board and falling Tet with those of `state`
+    pub fn load_state(&mut self, state: &GameState) -> Result<(), String> {
+        self.set_occupied(&state.get_blocks());
+
+        if let Some(piece) = &state.piece {
+            let kind = TetKind::by_name(&piece.kind).ok_or(format!("Unknown piece \"{}\"", piece.kind))?;
+            let tet = Tet::at_cells(kind, &piece.cells, self).ok_or(format!("Piece {} doesn't fit the rotation system", piece.kind))?;
+            tet.print(false, self);
+            self.tet = Some(tet);
+        }
+
+        Ok(())
+    }
+
+    /// Board and falling Tet as a fumen code
+    pub fn get_fumen(&self) -> Result<String, String> {
+        fumen::encode(&self.get_state())
+    }
+
+    /// Play exactly `kinds` in order, ending the game when they run out
+    pub fn set_sequence(&mut self, kinds: &[TetKind]) {
+        self.queue = kinds.iter().copied().collect();
+        self.sequence_fixed = true;
+        self.print_panel(PanelKind::Next);
+    }
+
+    /// Sequence is fixed and every Tet of it has been taken
+    pub fn is_out_of_tets(&self) -> bool {
+        self.sequence_fixed && self.queue.is_empty()
+    }
+
+    pub fn get_last_placed(&self) -> Option<&Tet> {
+        self.last_placed.as_ref()
+    }
+
+    pub fn get_hold(&self) -> Option<TetKind> {
+        self.hold
+    }
+
+    /// Hold was used for current Tet, so it can't be used again
+    pub fn is_hold_used(&self) -> bool {
+        self.hold_used
+    }
+
+    pub fn get_queue(&self) -> &VecDeque<TetKind> {
+        &self.queue
+    }
+
+    pub fn get_stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+    /// Snapshot of board, falling Tet, queue and score
+    pub fn get_state(&self) -> GameState {
+        let width = self.board.get_width() as usize;
+        let height = self.board.get_height() as usize;
+        let mut cells = vec![vec![EMPTY_CELL; width]; height];
+        for (x, y, block) in self.occupied.blocks().filter(|(_, y, _)| *y >= 0) {
+            cells[y as usize][x as usize] = match block.kind {
+                Some(kind) => kind.name().chars().next().unwrap(),
+                None => GARBAGE_CELL,
+            };
+        }
+
+        GameState {
+            board: cells.iter().map(|row| row.iter().collect()).collect(),
+            piece: self.tet.as_ref().map(|tet| PieceState {
+                kind: String::from(tet.kind.name()),
+                cells: tet.points_pos().iter().map(|point| (point.x, point.y)).collect(),
+            }),
+            queue: self.queue.iter().map(|kind| String::from(kind.name())).collect(),
+            hold: self.hold.map(|kind| String::from(kind.name())),
+            hold_used: self.hold_used,
+            score: self.score,
+            level: self.level,
+            lines: self.stats.lines,
+            incoming_garbage: self.incoming_garbage,
+        }
+    }
+
+    /// Garbage lines sent to opponent since last call
+    pub fn take_sent_garbage(&mut self) -> u16 {
+        std::mem::take(&mut self.sent_garbage)
+    }
+
+    /// Handle `tet` that was just placed on the board
+    ///
+    /// Returns false if garbage pushed blocks above the board
+    pub fn place_tet(&mut self, tet: &Tet) -> bool {
+        self.hold_used = false;
+        self.last_placed = Some(tet.clone());
+        let t_spin = tet.is_t_spin(self);
         let rows = self.clear_full_rows();
+        self.stats.add_piece(tet.kind, rows);
         self.update_score(rows as u32);
         self.update_level();
+        self.print_panel(PanelKind::Lines);
+        self.print_panel(PanelKind::Stats);
+
+        // Clearing rows cancels incoming garbage before sending any, while placing without clearing lets it rise
+        let attack = if t_spin { rows as u16 * 2 } else { ATTACK[(rows as usize).min(4)] };
+        let canceled = attack.min(self.incoming_garbage);
+        self.incoming_garbage -= canceled;
+        self.sent_garbage += attack - canceled;
+        let garbage_added = rows == 0 && self.incoming_garbage > 0;
+        if garbage_added && !self.insert_garbage() {
+            return false;
+        }
+        self.print_panel(PanelKind::Garbage);
+        self.print_panel(PanelKind::GarbageLeft);
+
+        if !self.config.display.animations {
+            return true;
+        }
+
+        if rows == 0 && !garbage_added {
+            self.animator.add(AnimationKind::LockFlash(tet.points_pos()), &self.board);
+        }
+
+        if t_spin {
+            let clear_names = ["", " SINGLE", " DOUBLE", " TRIPLE"];
+            let text = format!("T-SPIN{}!", clear_names[(rows as usize).min(3)]);
+            self.animator.add(AnimationKind::Callout(text), &self.board);
+        } else if rows >= 4 {
+            self.animator.add(AnimationKind::Callout(String::from("TETRIS!")), &self.board);
+        }
+
+        true
+    }
+
+    /// Move blocks up and fill rows below with incoming garbage, with a hole in the same column
+    ///
+    /// Returns false if blocks were pushed above the hidden rows
+    fn insert_garbage(&mut self) -> bool {
+        let lines = std::mem::take(&mut self.incoming_garbage) as usize;
+        let hole = self.garbage_rng.gen_range(0..self.board.get_width() as i16);
+        self.insert_garbage_rows(&vec![hole; lines])
+    }
+
+    /// Move blocks up and fill rows below with garbage, with a hole at each column of `holes`, from the top row down
+    ///
+    /// Returns false if blocks were pushed above the hidden rows
+    pub fn insert_garbage_rows(&mut self, holes: &[i16]) -> bool {
+        let width = self.board.get_width() as i16;
+        let rows = holes.iter()
+            .map(|hole| (0..width).map(|x| if x == *hole { None } else { Some(GameController::garbage_block()) }).collect())
+            .collect();
+
+        let fits = self.occupied.push_rows(rows);
+        self.print_occupied();
+        self.print_panel(PanelKind::GarbageLeft);
+        fits
+    }
+
+    /// Rows with garbage blocks left on the board
+    pub fn get_garbage_rows(&self) -> u16 {
+        let mut rows: Vec<i16> = self.occupied.blocks().filter(|(_, _, block)| block.kind.is_none()).map(|(_, y, _)| y).collect();
+        rows.sort();
+        rows.dedup();
+        rows.len() as u16
+    }
+
+    /// Play time, not counting time paused
+    pub fn get_play_ms(&self) -> u128 {
+        self.start_time.elapsed().unwrap().as_millis()
+    }
+
+    fn garbage_block() -> Block {
+        Block { color: GARBAGE_COLOR, kind: None }
     }
 
     fn set_fall_ms(&mut self) {
@@ -104,131 +543,164 @@ impl GameController {
         }
 
         let level = self.level - 1;
-        self.fall_ms = (INITIAL_FALL_MS as f32 / (1_f32 + level as f32 / LEVEL_SCALE as f32)).floor() as u16;
+        let gameplay = &self.config.gameplay;
+        self.fall_ms = (gameplay.initial_fall_ms as f32 / (1_f32 + level as f32 / gameplay.level_scale as f32)).floor() as u16;
     }
 
     fn update_score(&mut self, cleared_rows: u32) {
         self.score += 3_u32.pow(cleared_rows + 1);
-        self.print_score();
+        self.print_panel(PanelKind::Score);
     }
 
     fn update_level(&mut self) {
-        self.level = (self.score as f32 / LEVEL_AT_SCORE as f32).ceil() as u32;
-        self.print_level();
+        let level = (self.score as f32 / self.config.gameplay.level_at_score as f32).ceil() as u32;
+        self.level = level.max(self.config.gameplay.start_level as u32);
+        self.print_panel(PanelKind::Level);
         self.set_fall_ms();
     }
 
     /// Clear rows that span entire width of board
     ///
-    /// Returns cleared row count
+    /// Returns cleared line count, where a pair of rows cleared in big mode counts as one line
     fn clear_full_rows(&mut self) -> u8 {
-        let rows = self.get_row_count();
-
-        // Which rows should move down, and how far
-        let mut move_down_arr: Vec<u8> = vec![0; self.board.get_height() as usize];
-        let mut cleared_rows: u8 = 0;
-        for i in (0..move_down_arr.len()).rev() {
-            if rows[i] == self.board.get_width() {
-                cleared_rows += 1;
-            } else {
-                move_down_arr[i] = cleared_rows;
-            }
-        }
-
-        // Nothing was moved down, so no rows were cleared
-        if cleared_rows == 0 {
+        let group = if self.config.gameplay.big { 2 } else { 1 };
+        let full_rows = self.occupied.clear_full_rows(group);
+        if full_rows.is_empty() {
             return 0;
         }
 
-        // Move rows down and save rows to remove
-        let mut indexes_to_remove: Vec<usize> = Vec::new();
-        for i in 0..self.occupied.len() {
-            // NOTE: Panics if Y is negative (which it is when Tet spawns)
-            if rows[self.occupied[i].y as usize] == self.board.get_width() {
-                indexes_to_remove.push(i);
-            } else {
-                self.occupied[i].y += move_down_arr[self.occupied[i].y as usize] as i16;
-            }
-        }
+        let cleared_rows = (full_rows.len() / group) as u8;
 
-        // Remove rows
-        for i in (indexes_to_remove).iter().rev() {
-            self.occupied.remove(*i);
+        // Print updates, after flashing cleared rows if animated
+        if self.config.display.animations {
+            self.animator.add(AnimationKind::RowFlash(full_rows), &self.board);
+        } else {
+            self.print_occupied();
         }
 
-        // Print updates
-        self.print_occupied();
-
         cleared_rows
     }
 
-    pub fn collision_check(&self, points: [Point; 4], x: i16, y: i16) -> bool {
-        for i in 0..=3 {
-            let point = points[i];
-            if point.y + y == self.board.get_height() as i16 {
-                return true;
-            }
-
-            if point.x + x == self.board.get_width() as i16 || point.x + x == -1 {
-                return true;
-            }
-
-            for occ in &self.occupied {
-                if point.x + x == occ.x && point.y + y == occ.y {
-                    return true;
-                }
-            }
-        }
-
-        return false;
+    pub fn collision_check(&self, points: &[Point], x: i16, y: i16) -> bool {
+        points.iter().any(|point| self.occupied.is_blocked(point.x + x, point.y + y))
     }
 
     /// Display game over message and return on enter
-    fn game_over(&self, input_controller: &mut InputController) {
-        generic::move_cursor(28, 10);
-        println!("Game over");
+    fn game_over(&mut self, input_controller: &mut InputController) {
+        self.stats.duration_ms = self.start_time.elapsed().unwrap().as_millis() as u64;
+        stats_controller::add_to_lifetime(&self.stats);
 
-        highscore_controller::input_score(self.score, 28, 12);
-        menu_controller::score_menu_loop(input_controller);
-    }
+        let (message_x, message_y) = self.layout.get_message(2);
+        self.print_message(2, "Game over");
 
-    /// Get all rows with count of occupied spaces
-    fn get_row_count(&self) -> Vec<u16> {
-        let mut rows: Vec<u16> = vec![0; self.board.get_height() as usize];
-
-        for occ in &self.occupied {
-            // NOTE: Panics if Y is negative (which it is when Tet spawns)
-            rows[occ.y as usize] += 1;
-        }
-
-        rows
+        highscore_controller::input_score(self.score, message_x, message_y + 2, input_controller);
+        menu_controller::stats_menu_loop(input_controller, "Game statistics", &self.stats, false);
+        menu_controller::score_menu_loop(input_controller);
     }
 
     /// Clear board and print occupied points
     fn print_occupied(&self) {
         self.board.clear_board();
 
-        for occ in &self.occupied {
-            generic::set_color(occ.color);
-            generic::move_cursor(occ.x_width() as u16 + self.board.get_offset_x(), occ.y as u16 + self.board.get_offset_y());
-            print!("[]");
+        for (x, y, block) in self.occupied.blocks() {
+            generic::set_color(block.color);
+            self.board.print_cell(x, y, self.board.get_block_str(block.kind));
         }
 
         generic::set_color(Color::Reset);
         generic::move_cursor(0, 0);
     }
 
-    fn print_score(&self) {
-        generic::move_cursor(28, 2);
-        println!("Score: {:?}", self.score);
+    /// Print panel if it's part of the layout
+    fn print_panel(&self, kind: PanelKind) {
+        let Some((x, y)) = self.layout.get_panel(kind) else {
+            return;
+        };
+        if generic::headless() {
+            return;
+        }
+
+        generic::move_cursor(x, y);
+        match kind {
+            PanelKind::Score => print!("Score: {:?}", self.score),
+            PanelKind::Level => print!("Level: {:?}", self.level),
+            PanelKind::Lines => print!("Lines: {:?}", self.stats.lines),
+            PanelKind::Timer => print!("Time: {}:{:02}", self.timer_secs / 60, self.timer_secs % 60),
+            PanelKind::Stats => {
+                let seconds = self.start_time.elapsed().unwrap().as_secs_f32();
+                print!("Pieces: {:<10}", self.stats.pieces);
+                generic::move_cursor(x, y + 1);
+                print!("PPS: {:<13.2}", if seconds > 0.0 { self.stats.pieces as f32 / seconds } else { 0.0 });
+                generic::move_cursor(x, y + 2);
+                print!("Inputs/piece: {:<4.2}", self.stats.inputs_per_piece());
+            },
+            PanelKind::Garbage => {
+                print!("Garbage: ");
+                generic::set_color(Color::Red);
+                print!("{:<7}", "#".repeat((self.incoming_garbage as usize).min(7)));
+                generic::set_color(Color::Reset);
+            },
+            PanelKind::GarbageLeft => print!("Garbage left: {:<3}", self.get_garbage_rows()),
+            PanelKind::Hold => {
+                print!("Hold");
+                self.board.print_preview(self.hold, x, y + 1);
+            },
+            PanelKind::Next => {
+                print!("Next");
+                for i in 0..self.config.gameplay.preview_count as usize {
+                    self.board.print_preview(self.queue.get(i).copied(), x, y + 1 + i as u16 * (piece_set::get().get_preview_size().1 + 1));
+                }
+            },
+        }
+
+        generic::move_cursor(0, 0);
     }
 
-    fn print_level(&self) {
-        generic::move_cursor(28, 4);
-        println!("Level: {:?}", self.level);
+    /// Print timer panel when a second has passed
+    fn update_timer(&mut self) {
+        let seconds = self.start_time.elapsed().unwrap().as_secs();
+        if seconds != self.timer_secs {
+            self.timer_secs = seconds;
+            self.print_panel(PanelKind::Timer);
+        }
     }
 
     fn get_time_elapsed(&self) -> u128 {
         self.time.elapsed().unwrap().as_millis()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_overlapping_blocks_is_block_out() {
+        generic::set_headless(true);
+        let mut game = GameController::new(&Config::default());
+        let t = Tet::new(TetKind::by_name("T").unwrap(), &game);
+        assert!(!game.is_blocked_out(&t));
+
+        // One block in the hidden rows where the piece spawns
+        game.occupied.add_points(&t.points_pos()[..1]);
+        assert!(game.is_blocked_out(&t));
+    }
+
+    #[test]
+    fn next_piece_waits_for_cleared_rows_to_flash() {
+        generic::set_headless(true);
+        let mut game = GameController::new(&Config::default());
+        let o = TetKind::by_name("O").unwrap();
+        game.set_sequence(&[o, o]);
+        let cells: Vec<(i16, i16, Option<TetKind>)> = (0..10).filter(|x| *x != 4 && *x != 5).flat_map(|x| [(x, 18, None), (x, 19, None)]).collect();
+        game.set_occupied(&cells);
+
+        assert!(game.step(&[Action::HardDrop]));
+        assert!(game.occupied.is_empty());
+        assert!(game.get_tet().is_none());
+
+        assert!(game.step(&[Action::MoveLeft]));
+        assert!(game.get_tet().is_none());
+        assert_eq!(game.get_queue().len(), 1);
+    }
+}
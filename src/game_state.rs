@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use crate::tet::TetKind;
+
+/// Board cell without a block
+pub const EMPTY_CELL: char = '.';
+/// Board cell with a garbage block
+pub const GARBAGE_CELL: char = 'G';
+
+/// Snapshot of a game, sent to opponents and spectators
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GameState {
+    /// Rows from top to bottom, with a piece name like "T" for blocks, "G" for garbage and "." for empty cells
+    pub board: Vec<String>,
+    /// Falling piece, if any
+    pub piece: Option<PieceState>,
+    /// Names of upcoming pieces
+    pub queue: Vec<String>,
+    pub hold: Option<String>,
+    /// Hold was already used for falling piece
+    #[serde(default)]
+    pub hold_used: bool,
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+    /// Garbage lines waiting to be added
+    pub incoming_garbage: u16,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PieceState {
+    pub kind: String,
+    /// Board cells as (x, y), where y is negative above the board
+    pub cells: Vec<(i16, i16)>,
+}
+
+impl GameState {
+    /// Board with falling piece drawn into it, as rows of cells
+    pub fn get_cells(&self) -> Vec<Vec<char>> {
+        let mut cells: Vec<Vec<char>> = self.board.iter().map(|row| row.chars().collect()).collect();
+
+        if let Some(piece) = &self.piece {
+            let kind_char = piece.kind.chars().next().unwrap_or(EMPTY_CELL);
+            for (x, y) in &piece.cells {
+                if *x < 0 || *y < 0 {
+                    continue;
+                }
+
+                if let Some(cell) = cells.get_mut(*y as usize).and_then(|row| row.get_mut(*x as usize)) {
+                    *cell = kind_char;
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Blocks of board as (x, y, kind), where no kind is a garbage block
+    pub fn get_blocks(&self) -> Vec<(i16, i16, Option<TetKind>)> {
+        self.board.iter().enumerate()
+            .flat_map(|(y, row)| row.chars().enumerate().map(move |(x, cell)| (x as i16, y as i16, cell)))
+            .filter(|(_, _, cell)| *cell != EMPTY_CELL)
+            .map(|(x, y, cell)| (x, y, TetKind::by_name(&cell.to_string())))
+            .collect()
+    }
+
+    pub fn get_queue(&self) -> Vec<TetKind> {
+        self.queue.iter().filter_map(|name| TetKind::by_name(name)).collect()
+    }
+
+    pub fn get_hold(&self) -> Option<TetKind> {
+        self.hold.as_deref().and_then(TetKind::by_name)
+    }
+}
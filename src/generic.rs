@@ -1,9 +1,24 @@
-use std::io::stdout;
+use std::{io::stdout, env, sync::{OnceLock, atomic::{AtomicBool, Ordering}}};
 use crossterm::{cursor, terminal, Command, ExecutableCommand, style::{Color, SetForegroundColor}};
 
 use crate::input_controller::InputController;
 
+/// Nothing is drawn while headless, so games can run without a terminal
+static HEADLESS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_headless(headless: bool) {
+    HEADLESS.store(headless, Ordering::Relaxed);
+}
+
+pub fn headless() -> bool {
+    HEADLESS.load(Ordering::Relaxed)
+}
+
 pub fn term_command(command: impl Command) {
+    if headless() {
+        return;
+    }
+
     stdout().execute(command).unwrap();
 }
 
@@ -40,6 +55,16 @@ pub fn hide_cursor(hide: bool) {
     }
 }
 
+/// Colors are disabled by setting `NO_COLOR` environment variable, see https://no-color.org
+pub fn no_color() -> bool {
+    static NO_COLOR: OnceLock<bool> = OnceLock::new();
+    *NO_COLOR.get_or_init(|| env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()))
+}
+
 pub fn set_color(color: Color) {
+    if no_color() {
+        return;
+    }
+
     term_command(SetForegroundColor(color));
 }
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+use crossterm::style::Color;
+use crate::{point::Point, tet::TetKind};
+
+/// Block locked on the board
+#[derive(Clone, Copy)]
+pub struct Block {
+    pub color: Color,
+    /// Kind of piece this block is of, or none for garbage
+    pub kind: Option<TetKind>,
+}
+
+/// Locked blocks by row and column, so checking a cell or clearing a row doesn't search every block
+///
+/// Rows 0 and down are the visible board. Hidden rows above it, at negative y, hold pieces that spawn or lock above the board
+pub struct Grid {
+    width: u16,
+    /// Visible rows
+    height: u16,
+    /// Hidden rows above the visible rows
+    hidden: u16,
+    /// Hidden and visible rows from top to bottom, in a ring so rows are added at either end without moving the others
+    rows: VecDeque<Vec<Option<Block>>>,
+    /// Blocks in each row, so full rows are found without scanning them
+    counts: VecDeque<u16>,
+}
+
+impl Grid {
+    pub fn new(width: u16, height: u16, hidden: u16) -> Grid {
+        Grid {
+            width,
+            height,
+            hidden,
+            rows: VecDeque::from(vec![vec![None; width as usize]; (hidden + height) as usize]),
+            counts: VecDeque::from(vec![0; (hidden + height) as usize]),
+        }
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    /// Visible rows
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    /// Index in `rows` of board row `y`, if it's part of the grid
+    fn row_index(&self, y: i16) -> Option<usize> {
+        let index = y + self.hidden as i16;
+        if index < 0 || index >= self.rows.len() as i16 {
+            return None;
+        }
+
+        Some(index as usize)
+    }
+
+    /// Block at `x`, `y`, or none if the cell is empty or outside the grid
+    pub fn get(&self, x: i16, y: i16) -> Option<Block> {
+        if x < 0 || x >= self.width as i16 {
+            return None;
+        }
+
+        self.rows[self.row_index(y)?][x as usize]
+    }
+
+    /// Cell is outside the walls or below the floor, or has a block
+    ///
+    /// Cells above the hidden rows are empty
+    pub fn is_blocked(&self, x: i16, y: i16) -> bool {
+        if x < 0 || x >= self.width as i16 || y >= self.height as i16 {
+            return true;
+        }
+
+        self.get(x, y).is_some()
+    }
+
+    /// Set or remove block at `x`, `y`, ignoring cells outside the grid
+    pub fn set(&mut self, x: i16, y: i16, block: Option<Block>) {
+        let Some(index) = self.row_index(y).filter(|_| x >= 0 && x < self.width as i16) else {
+            return;
+        };
+
+        let cell = &mut self.rows[index][x as usize];
+        match (cell.is_some(), block.is_some()) {
+            (false, true) => self.counts[index] += 1,
+            (true, false) => self.counts[index] -= 1,
+            _ => (),
+        }
+        *cell = block;
+    }
+
+    /// Add blocks of locked piece `points`
+    pub fn add_points(&mut self, points: &[Point]) {
+        for point in points {
+            self.set(point.x, point.y, Some(Block { color: point.color, kind: point.kind }));
+        }
+    }
+
+    /// Remove every block
+    pub fn clear(&mut self) {
+        *self = Grid::new(self.width, self.height, self.hidden);
+    }
+
+    /// Remove rows that span the entire width, moving rows above them down
+    ///
+    /// Rows are grouped by `group` counted up from the floor, and only removed when their whole group is full,
+    /// so rows of big blocks clear together. Returns the removed rows, as they were numbered before removing
+    pub fn clear_full_rows(&mut self, group: usize) -> Vec<i16> {
+        let len = self.counts.len();
+        let group_full = |index: usize| {
+            let bottom = len - 1 - (len - 1 - index) / group * group;
+            (bottom.saturating_sub(group - 1)..=bottom).all(|row| self.counts[row] == self.width)
+        };
+        let full_rows: Vec<usize> = (0..len).filter(|index| group_full(*index)).collect();
+
+        // Removed from the top down, so rows above a removed row are moved down before the next one is removed
+        for index in &full_rows {
+            self.rows.remove(*index);
+            self.rows.push_front(vec![None; self.width as usize]);
+            self.counts.remove(*index);
+            self.counts.push_front(0);
+        }
+
+        full_rows.iter().map(|index| *index as i16 - self.hidden as i16).collect()
+    }
+
+    /// Move rows up and add `rows` below them, from the top row down
+    ///
+    /// Returns false if blocks were pushed above the hidden rows, which removes them
+    pub fn push_rows(&mut self, rows: Vec<Vec<Option<Block>>>) -> bool {
+        let pushed_out = self.counts.iter().take(rows.len()).any(|count| *count > 0);
+
+        for row in rows {
+            self.counts.pop_front();
+            self.counts.push_back(row.iter().filter(|cell| cell.is_some()).count() as u16);
+            self.rows.pop_front();
+            self.rows.push_back(row);
+        }
+
+        !pushed_out
+    }
+
+    /// Every block, including hidden ones, as (x, y, block)
+    pub fn blocks(&self) -> impl Iterator<Item = (i16, i16, Block)> + '_ {
+        let hidden = self.hidden as i16;
+        self.rows.iter().enumerate().flat_map(move |(index, row)| {
+            row.iter().enumerate().filter_map(move |(x, cell)| cell.map(|block| (x as i16, index as i16 - hidden, block)))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|count| *count == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block() -> Option<Block> {
+        Some(Block { color: Color::White, kind: None })
+    }
+
+    /// Row with blocks in every column but `holes`
+    fn row(width: u16, holes: &[i16]) -> Vec<Option<Block>> {
+        (0..width as i16).map(|x| if holes.contains(&x) { None } else { block() }).collect()
+    }
+
+    #[test]
+    fn full_rows_are_cleared_and_rows_above_move_down() {
+        let mut grid = Grid::new(4, 6, 2);
+        for x in 0..4 {
+            grid.set(x, 5, block());
+            grid.set(x, 3, block());
+        }
+        grid.set(1, 4, block());
+        grid.set(2, 2, block());
+        grid.set(0, -1, block());
+
+        assert_eq!(grid.clear_full_rows(1), vec![3, 5]);
+
+        let blocks: Vec<(i16, i16)> = grid.blocks().map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(blocks, vec![(0, 1), (2, 4), (1, 5)]);
+        assert_eq!(grid.counts, [0, 0, 0, 1, 0, 0, 1, 1]);
+        assert!(grid.clear_full_rows(1).is_empty());
+    }
+
+    #[test]
+    fn full_rows_in_pairs_clear_only_with_their_pair() {
+        let mut grid = Grid::new(4, 6, 2);
+        for x in 0..4 {
+            grid.set(x, 5, block());
+            grid.set(x, 4, block());
+            grid.set(x, 3, block());
+        }
+
+        // Rows 2 and 3 make a pair, of which only row 3 is full
+        assert_eq!(grid.clear_full_rows(2), vec![4, 5]);
+        assert_eq!(grid.counts, [0, 0, 0, 0, 0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn pushed_rows_are_added_below() {
+        let mut grid = Grid::new(4, 4, 1);
+        grid.set(3, 3, block());
+
+        assert!(grid.push_rows(vec![row(4, &[0]), row(4, &[1])]));
+
+        assert!(grid.get(3, 1).is_some());
+        assert!(grid.get(0, 2).is_none() && grid.get(1, 2).is_some());
+        assert!(grid.get(1, 3).is_none() && grid.get(0, 3).is_some());
+        assert_eq!(grid.counts, [0, 0, 1, 3, 3]);
+    }
+
+    #[test]
+    fn pushing_blocks_above_hidden_rows_returns_false() {
+        let mut grid = Grid::new(4, 4, 1);
+        grid.set(2, 0, block());
+
+        assert!(!grid.push_rows(vec![row(4, &[0]), row(4, &[0])]));
+
+        // Block was pushed to the hidden row and then out of the grid
+        assert!(grid.blocks().all(|(_, y, _)| y >= 2));
+        assert_eq!(grid.counts, [0, 0, 0, 3, 3]);
+    }
+}
@@ -1,11 +1,13 @@
-use std::{io, fs, path::Path};
+use std::{fs, path::Path};
 
-use crate::{generic, input_controller::InputController};
+use crate::{generic, input_controller::InputController, text_field::TextField};
 
 const FILE: &str = "scores.txt";
+const LAST_NAME_FILE: &str = "last_name.txt";
 const MAX_SCORES: usize = 10;
+const MAX_NAME_LEN: usize = 16;
 
-pub fn input_score(score: u32, cursor_x: u16, cursor_y: u16) {
+pub fn input_score(score: u32, cursor_x: u16, cursor_y: u16, input_controller: &mut InputController) {
     generic::move_cursor(cursor_x, cursor_y);
 
     if score == 0 {
@@ -31,22 +33,41 @@ pub fn input_score(score: u32, cursor_x: u16, cursor_y: u16) {
     print!("> ");
     let cursor_x = cursor_x + 2;
 
-    generic::hide_cursor(false);
+    let mut text_field = TextField::new(MAX_NAME_LEN, allowed_name_char);
+    text_field.set_value(&read_last_name());
 
-    // TODO: Name length limit
-    // TODO: Only allow letters, numbers, and whitespace
     let mut name = String::new();
     while str::is_empty(name.trim()) {
-        generic::move_cursor(cursor_x, cursor_y);
-        io::stdin().read_line(&mut name).unwrap();
+        if !input_controller.read_text(&mut text_field, cursor_x, cursor_y) {
+            // Cancelled, so don't save score
+            return;
+        }
+
+        name = text_field.get_value();
     }
 
-    generic::hide_cursor(true);
+    let name = String::from(name.trim());
+    write_last_name(&name);
 
     name_scores.push(NameScore { name, score });
     write_scores(name_scores);
 }
 
+/// Letters, numbers, and whitespace, so name can't break the score file format
+fn allowed_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == ' '
+}
+
+fn read_last_name() -> String {
+    fs::read_to_string(LAST_NAME_FILE).unwrap_or_default()
+}
+
+fn write_last_name(name: &str) {
+    if fs::write(LAST_NAME_FILE, name).is_err() {
+        generic::error_print(&format!("Failed to write to file {}", LAST_NAME_FILE));
+    }
+}
+
 pub fn write_scores(mut name_scores: Vec<NameScore>) {
     sort_scores(&mut name_scores);
 
@@ -59,7 +80,7 @@ pub fn write_scores(mut name_scores: Vec<NameScore>) {
         name_scores_str.push_str(&format!("{}:{};", &name_score.name.trim(), name_score.score));
     }
 
-    if let Err(_) = fs::write(FILE, name_scores_str) {
+    if fs::write(FILE, name_scores_str).is_err() {
         generic::error_print(&format!("Failed to write to score file {}", FILE));
     }
 }
@@ -83,11 +104,11 @@ pub fn read_scores() -> Vec<NameScore> {
 pub fn parse_score_str(score_str: &str) -> Vec<NameScore> {
     let mut name_scores: Vec<NameScore> = Vec::new();
 
-    if str::is_empty(&score_str) || !str::contains(&score_str, ';') {
+    if str::is_empty(score_str) || !str::contains(score_str, ';') {
         return name_scores;
     }
 
-    for single_score_str in score_str.split(';').into_iter() {
+    for single_score_str in score_str.split(';') {
         if !single_score_str.contains(':') {
             continue;
         }
@@ -113,8 +134,8 @@ pub fn cleanup_scores(name_scores: &mut Vec<NameScore>) {
     }
 }
 
-pub fn sort_scores(name_scores: &mut Vec<NameScore>) {
-    name_scores.sort_by(|a, b| b.score.cmp(&a.score));
+pub fn sort_scores(name_scores: &mut [NameScore]) {
+    name_scores.sort_by_key(|name_score| std::cmp::Reverse(name_score.score));
 }
 
 pub struct NameScore {
@@ -1,11 +1,12 @@
 use std::time::Duration;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crate::key::Key;
 
 pub struct Input {
-    // TODO: maybe make a key struct
     // TODO: Rewrite `pressed`, because it doesn't work as expected with Enter or chars
-    down: Vec<KeyCode>,
-    pressed: Vec<KeyCode>,
+    down: Vec<Key>,
+    pressed: Vec<Key>,
+    typed: Vec<KeyCode>,
 }
 
 impl Input {
@@ -13,36 +14,61 @@ impl Input {
         Input {
             down: Vec::new(),
             pressed: Vec::new(),
+            typed: Vec::new(),
         }
     }
 
-    /// Captures input and stores it
+    /// Captures input and stores it, waiting up to `timeout` for it to arrive, or until it does if none
     ///
     /// Must be run every cycle, or input won't be read as expected
-    pub fn capture_input(&mut self) {
+    pub fn capture_input(&mut self, timeout: Option<Duration>) {
         self.pressed.clear();
-        if !crossterm::event::poll(Duration::from_secs(0)).unwrap() {
+        self.typed.clear();
+        if timeout.is_some_and(|timeout| !crossterm::event::poll(timeout).unwrap()) {
             return
         }
 
-        if let Event::Key(KeyEvent { code, modifiers: _, kind, .. }) = crossterm::event::read().unwrap() {
-            self.handle_key_event(code, kind);
+        if let Event::Key(KeyEvent { code, modifiers, kind, .. }) = crossterm::event::read().unwrap() {
+            self.handle_key_event(code, modifiers, kind);
         }
     }
 
-    pub fn key_down(&self, code: KeyCode) -> bool {
-        self.get_down_index(code).is_ok()
+    /// Key with given code was pressed, with any modifiers
+    pub fn key_pressed(&self, code: KeyCode) -> bool {
+        self.pressed.iter().any(|pressed| pressed.same_code(code))
     }
 
-    pub fn key_pressed(&self, code: KeyCode) -> bool {
-        self.get_pressed_index(code).is_ok()
+    /// Key was down with exactly the same modifiers when pressed
+    pub fn exact_key_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    /// Key was pressed with exactly the same modifiers
+    pub fn exact_key_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Keys pressed this cycle, with modifiers
+    pub fn pressed_keys(&self) -> &[Key] {
+        &self.pressed
+    }
+
+    /// Keys typed this cycle, including repeats of keys already held down
+    ///
+    /// Unlike `key_pressed`, this doesn't depend on release events, so it's suited for text entry
+    pub fn typed_keys(&self) -> &[KeyCode] {
+        &self.typed
     }
 
-    fn handle_key_event(&mut self, code: KeyCode, kind: KeyEventKind) {
+    fn handle_key_event(&mut self, code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) {
+        if kind != KeyEventKind::Release {
+            self.typed.push(code);
+        }
+
         if kind == KeyEventKind::Release {
             self.handle_key_event_release(code);
         } else if kind == KeyEventKind::Press {
-            self.handle_key_event_press(code);
+            self.handle_key_event_press(Key::new(code, modifiers));
         }
     }
 
@@ -53,31 +79,23 @@ impl Input {
         }
     }
 
-    fn handle_key_event_press(&mut self, code: KeyCode) {
-        if self.get_down_index(code).is_ok() {
+    fn handle_key_event_press(&mut self, key: Key) {
+        if self.get_down_index(key.code).is_ok() {
             return
         }
 
-        self.pressed.push(code);
-        self.down.push(code);
+        self.pressed.push(key);
+        self.down.push(key);
     }
 
-    /// Get index of `code` in `key_vec`
-    fn get_key_index(key_vec: &Vec<KeyCode>, code: KeyCode) -> Result<usize, usize> {
-        for i in 0..key_vec.len() {
-            if key_vec[i] == code {
+    /// Get index of key with `code` in `down`, ignoring modifiers
+    fn get_down_index(&self, code: KeyCode) -> Result<usize, usize> {
+        for (i, key) in self.down.iter().enumerate() {
+            if key.same_code(code) {
                 return Ok(i);
             }
         }
 
         Err(0)
     }
-
-    fn get_down_index(&self, code: KeyCode) -> Result<usize, usize> {
-        Input::get_key_index(&self.down, code)
-    }
-
-    fn get_pressed_index(&self, code: KeyCode) -> Result<usize, usize> {
-        Input::get_key_index(&self.pressed, code)
-    }
 }
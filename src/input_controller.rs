@@ -1,66 +1,155 @@
 use crossterm::event::KeyCode;
-use crate::{input::Input, input_mem::InputMem};
+use crate::{input::Input, input_mem::InputMem, ticker::Ticker, text_field::{TextField, TextFieldEvent}, generic, config::{Config, KeysConfig}, action::Action, key::Key};
 
 pub struct InputController {
     input: Input,
-    input_memory: [InputMem; 3], // TODO: Refactor to vec
+    /// Keys of each player, with one player outside of versus
+    players: Vec<PlayerKeys>,
+    /// Paces cycles, so loops wait for input or the next cycle instead of spinning
+    ticker: Ticker,
+}
+
+/// Key bindings and repeat state of one player
+struct PlayerKeys {
+    /// Keys bound to each action, indexed by `Action::index`
+    bindings: Vec<Vec<Key>>,
+    /// Repeat state of actions that repeat while held
+    input_memory: Vec<InputMem>,
+}
+
+impl PlayerKeys {
+    fn new(keys: &KeysConfig, config: &Config) -> PlayerKeys {
+        PlayerKeys {
+            bindings: Action::ALL.iter().map(|action| keys.get_keys(*action)).collect(),
+            input_memory: Action::ALL.iter()
+                .filter_map(|action| Some(InputMem::new(*action, config.get_repeat(*action)?)))
+                .collect(),
+        }
+    }
 }
 
 impl InputController {
-    pub fn new() -> InputController {
+    pub fn new(config: &Config) -> InputController {
         InputController {
             input: Input::new(),
-            input_memory: [
-                InputMem::new(KeyCode::Left),
-                InputMem::new(KeyCode::Right),
-                InputMem::new(KeyCode::Down)
-            ]
+            players: vec![PlayerKeys::new(&config.keys, config)],
+            ticker: Ticker::new(config.gameplay.tick_rate),
         }
     }
 
+    /// Use key bindings and repeat timings from `config`
+    pub fn apply_config(&mut self, config: &Config) {
+        self.players = vec![PlayerKeys::new(&config.keys, config)];
+        self.ticker = Ticker::new(config.gameplay.tick_rate);
+    }
+
+    /// Use key bindings of both versus players, until `apply_config` is called
+    pub fn apply_versus_config(&mut self, config: &Config) {
+        self.players = vec![
+            PlayerKeys::new(&config.versus.player_1, config),
+            PlayerKeys::new(&config.versus.player_2, config),
+        ];
+    }
+
     /// Call at start of every cycle
+    ///
+    /// Waits until input arrives or the next tick is due, so a cycle runs at least once per tick but never spins
     pub fn update(&mut self) {
-        self.input.capture_input();
+        self.ticker.wait(&mut self.input);
     }
 
     /// Call at end of every cyle
     pub fn end_update(&mut self) {
-        for mem in &mut self.input_memory {
-            mem.set_released(&self.input);
+        for player in 0..self.players.len() {
+            for i in 0..self.players[player].input_memory.len() {
+                let down = self.action_down(player, self.players[player].input_memory[i].get_action());
+                self.players[player].input_memory[i].set_released(down);
+            }
         }
     }
 
-    pub fn key_hold(&mut self, code: KeyCode) -> bool {
-        let index: usize = match code {
-            KeyCode::Left => 0,
-            KeyCode::Right => 1,
-            KeyCode::Down => 2,
-            _ => 255
-        };
+    /// Actions of `player` triggered this cycle, with held actions limited by their repeat timing
+    pub fn player_actions(&mut self, player: usize) -> Vec<Action> {
+        Action::ALL.iter().copied().filter(|action| self.action_hold(player, *action)).collect()
+    }
 
-        if index == 255 {
-            return false;
-        }
+    /// Actions of `player` pressed this cycle, ignoring keys held down since earlier cycles
+    pub fn pressed_actions(&self, player: usize) -> Vec<Action> {
+        Action::ALL.iter().copied().filter(|action| self.player_action_pressed(player, *action)).collect()
+    }
+
+    /// Action is held down, limited to repeat by its repeat timing
+    ///
+    /// Actions without repeat timing only trigger when pressed
+    fn action_hold(&mut self, player: usize, action: Action) -> bool {
+        let index = match self.players[player].input_memory.iter().position(|mem| mem.get_action() == action) {
+            Some(index) => index,
+            None => return self.player_action_pressed(player, action),
+        };
 
-        let down = self.input.key_down(code) && self.input_memory[index].allowed();
+        let down = self.action_down(player, action) && self.players[player].input_memory[index].allowed();
         if down {
-            self.input_memory[index].set_time();
+            self.players[player].input_memory[index].set_time();
         }
 
         down
     }
 
+    fn action_down(&self, player: usize, action: Action) -> bool {
+        self.players[player].bindings[action.index()].iter().any(|key| self.input.exact_key_down(*key))
+    }
+
+    fn player_action_pressed(&self, player: usize, action: Action) -> bool {
+        self.players[player].bindings[action.index()].iter().any(|key| self.input.exact_key_pressed(*key))
+    }
+
+    /// Action was pressed by any player
+    pub fn action_pressed(&self, action: Action) -> bool {
+        (0..self.players.len()).any(|player| self.player_action_pressed(player, action))
+    }
+
     pub fn key_pressed(&self, code: KeyCode) -> bool {
         self.input.key_pressed(code)
     }
 
+    /// Keys pressed this cycle, with modifiers
+    pub fn pressed_keys(&self) -> &[Key] {
+        self.input.pressed_keys()
+    }
+
+    /// Let user edit `text_field` at given position until ENTER or ESC is pressed
+    ///
+    /// Returns true if submitted with ENTER, false if cancelled with ESC
+    pub fn read_text(&mut self, text_field: &mut TextField, x: u16, y: u16) -> bool {
+        generic::hide_cursor(false);
+        text_field.print(x, y);
+
+        let submitted = loop {
+            self.update();
+
+            match text_field.update(&self.input) {
+                TextFieldEvent::Changed => text_field.print(x, y),
+                TextFieldEvent::Submit => break true,
+                TextFieldEvent::Cancel => break false,
+                TextFieldEvent::None => (),
+            }
+
+            self.end_update();
+        };
+
+        generic::hide_cursor(true);
+        submitted
+    }
+
+    /// Wait until ENTER is pressed, blocking between keys
     pub fn wait_for_enter() {
-        let mut input_controller = InputController::new();
+        let mut input = Input::new();
+        let mut ticker = Ticker::until_input();
 
         loop {
-            input_controller.update();
+            ticker.wait(&mut input);
 
-            if input_controller.key_pressed(KeyCode::Enter) {
+            if input.key_pressed(KeyCode::Enter) {
                 return;
             }
         }
@@ -1,31 +1,51 @@
 use std::time::SystemTime;
-use crossterm::event::KeyCode;
-use crate::input::Input;
+use crate::{action::Action, config::RepeatConfig};
 
 pub struct InputMem {
-    code: KeyCode,
+    action: Action,
     time: SystemTime,
     released: bool,
+    /// Action has triggered since initial press
+    repeating: bool,
+    delay_ms: u16,
+    interval_ms: u16,
 }
 
 impl InputMem {
-    pub fn new(code: KeyCode) -> InputMem {
+    pub fn new(action: Action, repeat: &RepeatConfig) -> InputMem {
         InputMem {
-            code,
+            action,
             time: SystemTime::now(),
             released: true,
+            repeating: false,
+            delay_ms: repeat.delay_ms,
+            interval_ms: repeat.interval_ms,
         }
     }
 
+    pub fn get_action(&self) -> Action {
+        self.action
+    }
+
+    /// Allowed on initial press, then after `delay_ms`, then every `interval_ms`
     pub fn allowed(&self) -> bool {
-        self.released || self.time.elapsed().unwrap().as_millis() > 100
+        if self.released {
+            return true;
+        }
+
+        let wait_ms = if self.repeating { self.interval_ms } else { self.delay_ms };
+        self.time.elapsed().unwrap().as_millis() >= wait_ms as u128
     }
 
     pub fn set_time(&mut self) {
+        self.repeating = !self.released;
         self.time = SystemTime::now();
     }
 
-    pub fn set_released(&mut self, input: &Input) {
-        self.released = !input.key_down(self.code);
+    pub fn set_released(&mut self, down: bool) {
+        self.released = !down;
+        if self.released {
+            self.repeating = false;
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,135 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// Key code with the modifiers held when it was pressed
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Create normalized key, so letters are lowercase with SHIFT as modifier,
+    /// and other characters don't carry SHIFT, since it's already part of the character
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let modifiers = modifiers & (KeyModifiers::SHIFT | KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        if let KeyCode::Char(c) = code {
+            if c.is_uppercase() {
+                return Key {
+                    code: KeyCode::Char(c.to_lowercase().next().unwrap_or(c)),
+                    modifiers: modifiers | KeyModifiers::SHIFT,
+                };
+            }
+
+            if !c.is_alphabetic() {
+                return Key { code, modifiers: modifiers - KeyModifiers::SHIFT };
+            }
+        }
+
+        Key { code, modifiers }
+    }
+
+    /// Key code matches, ignoring modifiers
+    pub fn same_code(&self, code: KeyCode) -> bool {
+        self.code == Key::new(code, KeyModifiers::NONE).code
+    }
+
+    /// Parse key like "Left", "Space", "F1", "x" or combinations like "Ctrl+Shift+Z"
+    pub fn parse(key: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = key.split('+').collect();
+
+        // Allow binding "+" itself, like "Ctrl++"
+        let name = if key.ends_with("++") || key == "+" {
+            parts.truncate(parts.len().saturating_sub(2));
+            "+"
+        } else {
+            parts.pop()?
+        };
+
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "shift" => KeyModifiers::SHIFT,
+                "alt" => KeyModifiers::ALT,
+                _ => return None,
+            };
+        }
+
+        Some(Key::new(parse_code(name)?, modifiers))
+    }
+
+    /// Name that can be parsed back with `Key::parse`
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            name.push_str("Shift+");
+        }
+
+        name.push_str(&code_name(self.code));
+        name
+    }
+}
+
+fn parse_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let code = match name.to_lowercase().as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => {
+            let number: u8 = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&number) {
+                return None;
+            }
+            KeyCode::F(number)
+        }
+    };
+
+    Some(code)
+}
+
+fn code_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(number) => format!("F{}", number),
+        KeyCode::Left => String::from("Left"),
+        KeyCode::Right => String::from("Right"),
+        KeyCode::Up => String::from("Up"),
+        KeyCode::Down => String::from("Down"),
+        KeyCode::Esc => String::from("Esc"),
+        KeyCode::Enter => String::from("Enter"),
+        KeyCode::Tab => String::from("Tab"),
+        KeyCode::Backspace => String::from("Backspace"),
+        KeyCode::Delete => String::from("Delete"),
+        KeyCode::Insert => String::from("Insert"),
+        KeyCode::Home => String::from("Home"),
+        KeyCode::End => String::from("End"),
+        KeyCode::PageUp => String::from("PageUp"),
+        KeyCode::PageDown => String::from("PageDown"),
+        _ => String::from("?"),
+    }
+}
@@ -0,0 +1,195 @@
+use crossterm::terminal;
+use serde::{Deserialize, Serialize};
+use crate::{config::Config, piece_set};
+
+/// Space left of the board, or left of left panels
+const MARGIN_X: u16 = 3;
+const BOARD_Y: u16 = 2;
+const PANEL_WIDTH: u16 = 20;
+/// Rows for messages like pause, game over and callouts
+const MESSAGE_HEIGHT: u16 = 6;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelKind {
+    Hold,
+    Next,
+    Score,
+    Level,
+    Lines,
+    Timer,
+    Stats,
+    /// Garbage lines waiting to be added, in versus
+    Garbage,
+    /// Rows with garbage left to clear, in dig
+    GarbageLeft,
+}
+
+/// Which panels to show left and right of the board, from top to bottom
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub left: Vec<PanelKind>,
+    pub right: Vec<PanelKind>,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            left: vec![PanelKind::Hold],
+            right: vec![PanelKind::Score, PanelKind::Level, PanelKind::Lines, PanelKind::Timer, PanelKind::Next],
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Default layout of game `mode`
+    ///
+    /// Versus leaves out panels, to fit two boards, and dig shows time and garbage instead of score
+    pub fn default_for(mode: &str) -> LayoutConfig {
+        match mode {
+            "dig" => LayoutConfig {
+                left: vec![PanelKind::Hold],
+                right: vec![PanelKind::Timer, PanelKind::GarbageLeft, PanelKind::Lines, PanelKind::Next],
+            },
+            "versus" => LayoutConfig {
+                left: vec![PanelKind::Hold],
+                right: vec![PanelKind::Lines, PanelKind::Garbage, PanelKind::Next],
+            },
+            _ => LayoutConfig::default(),
+        }
+    }
+}
+
+struct PlacedPanel {
+    kind: PanelKind,
+    x: u16,
+    y: u16,
+}
+
+/// Terminal positions of board, panels and messages
+pub struct Layout {
+    board_x: u16,
+    board_y: u16,
+    panels: Vec<PlacedPanel>,
+    message_x: u16,
+    message_y: u16,
+}
+
+impl Layout {
+    /// Place panels of layout for `mode` around board, fitting them to terminal size
+    ///
+    /// Left panels move right of the board if the terminal is too narrow,
+    /// and right panels continue in another column if the terminal is too short.
+    /// The terminal size is only read here, so a layout doesn't follow later resizes
+    pub fn new(config: &Config, mode: &str) -> Layout {
+        let (terminal_width, _) = terminal::size().unwrap_or((80, 24));
+        Layout::new_at(config, mode, 0, terminal_width)
+    }
+
+    /// Place layout in the `width` columns starting at column `x`
+    pub fn new_at(config: &Config, mode: &str, area_x: u16, width: u16) -> Layout {
+        let layout_config = config.get_layout(mode);
+        let (_, terminal_height) = terminal::size().unwrap_or((80, 24));
+
+        let board_width = config.display.board_width * config.display.x_width;
+        let board_height = config.display.board_height;
+        let available_height = terminal_height.max(BOARD_Y + board_height + 1);
+
+        let mut left = layout_config.left.clone();
+        let mut right = layout_config.right.clone();
+
+        let left_width = left.iter().map(|kind| Layout::panel_width(*kind, config)).max().unwrap_or(0);
+        let needed_width = MARGIN_X + left_width + 2 + board_width + 3 + PANEL_WIDTH;
+        if !left.is_empty() && needed_width > width {
+            left.append(&mut right);
+            right = left;
+            left = Vec::new();
+        }
+
+        let mut panels: Vec<PlacedPanel> = Vec::new();
+
+        // Left column
+        let mut board_x = area_x + MARGIN_X + 2;
+        if !left.is_empty() {
+            let left_width = left.iter().map(|kind| Layout::panel_width(*kind, config)).max().unwrap_or(0);
+            let mut y = BOARD_Y;
+            for kind in left {
+                panels.push(PlacedPanel { kind, x: area_x + MARGIN_X - 2, y });
+                y += Layout::panel_height(kind, config) + 1;
+            }
+            board_x += left_width;
+        }
+
+        // Right columns, with messages below last panel
+        let mut x = board_x + board_width + 3;
+        let mut y = BOARD_Y;
+        for kind in right {
+            let height = Layout::panel_height(kind, config);
+            if y > BOARD_Y && y + height > available_height {
+                x += PANEL_WIDTH;
+                y = BOARD_Y;
+            }
+
+            panels.push(PlacedPanel { kind, x, y });
+            y += height + 1;
+        }
+
+        if y > BOARD_Y && y + MESSAGE_HEIGHT > available_height {
+            x += PANEL_WIDTH;
+            y = BOARD_Y;
+        }
+
+        Layout {
+            board_x,
+            board_y: BOARD_Y,
+            panels,
+            message_x: x,
+            message_y: y,
+        }
+    }
+
+    pub fn get_board_x(&self) -> u16 {
+        self.board_x
+    }
+
+    pub fn get_board_y(&self) -> u16 {
+        self.board_y
+    }
+
+    /// Position of `kind`, if it's part of the layout
+    pub fn get_panel(&self, kind: PanelKind) -> Option<(u16, u16)> {
+        self.panels.iter().find(|panel| panel.kind == kind).map(|panel| (panel.x, panel.y))
+    }
+
+    /// Position of `line` in the message area
+    pub fn get_message(&self, line: u16) -> (u16, u16) {
+        (self.message_x, self.message_y + line)
+    }
+
+    /// First column right of the layout
+    pub fn get_right_x(&self) -> u16 {
+        self.message_x + PANEL_WIDTH
+    }
+
+    fn panel_height(kind: PanelKind, config: &Config) -> u16 {
+        let preview_rows = piece_set::get().get_preview_size().1;
+        match kind {
+            // Title and rows of each piece preview, with a row between previews
+            PanelKind::Hold => 1 + preview_rows,
+            PanelKind::Next => 1 + (config.gameplay.preview_count as u16 * (preview_rows + 1)).saturating_sub(1),
+            PanelKind::Stats => 3,
+            _ => 1,
+        }
+    }
+
+    fn panel_width(kind: PanelKind, config: &Config) -> u16 {
+        match kind {
+            PanelKind::Hold | PanelKind::Next => (piece_set::get().get_preview_size().0 * config.display.x_width).max(4),
+            PanelKind::Stats => 18,
+            PanelKind::Garbage => 16,
+            PanelKind::GarbageLeft => 17,
+            _ => 12,
+        }
+    }
+}
@@ -1,33 +1,171 @@
-use crate::{game_controller::GameController, input_controller::InputController, menu_controller::MenuOption};
+use crate::{game_controller::GameController, input_controller::InputController, menu_controller::MenuOption, config::Config, publisher::Publisher, game_state::GameState, bot::{Autoplayer, Bot}, external_bot::ExternalBot};
 
 mod generic;
 mod tet;
 mod point;
+mod grid;
 mod input;
 mod board;
 mod input_mem;
+mod ticker;
 mod game_controller;
 mod input_controller;
 mod menu_controller;
 mod highscore_controller;
+mod text_field;
+mod stats_controller;
+mod config;
+mod key;
+mod action;
+mod theme;
+mod animation;
+mod layout;
+mod menu;
+mod randomizer;
+mod versus_controller;
+mod game_state;
+mod remote_view;
+mod net;
+mod net_controller;
+mod publisher;
+mod spectator_controller;
+mod bot;
+mod bot_controller;
+mod external_bot;
+mod finesse;
+mod finesse_controller;
+mod puzzle;
+mod puzzle_controller;
+mod dig_controller;
+mod fumen;
+mod piece_set;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let Ok(spectate_address) = flag_value(&args, "--spectate") else {
+        eprintln!("Usage: tetris --spectate <address>");
+        return;
+    };
+    let Ok(fumen_code) = flag_value(&args, "--fumen") else {
+        eprintln!("Usage: tetris --fumen <code>");
+        return;
+    };
+    let Ok(bot_command) = flag_value(&args, "--bot") else {
+        eprintln!("Usage: tetris --bot <command>");
+        return;
+    };
+
+    if let Some(index) = args.iter().position(|arg| arg == "--headless") {
+        let games = match args.get(index + 1).filter(|games| !games.starts_with("--")).map(|games| games.parse()) {
+            Some(Ok(games)) => games,
+            Some(Err(_)) => {
+                eprintln!("Usage: tetris --headless [games]");
+                return;
+            },
+            None => 10,
+        };
+
+        let (config, mut errors) = config::load();
+        errors.extend(piece_set::init(&config.gameplay.pieces));
+        if !errors.is_empty() {
+            eprintln!("Config problems, using defaults for these:\n{}", errors.join("\n"));
+        }
+
+        let bot: Result<Box<dyn Autoplayer>, String> = match &bot_command {
+            Some(command) => ExternalBot::spawn(command, &config, 0).map(|bot| Box::new(bot) as Box<dyn Autoplayer>),
+            None => Ok(Box::new(Bot::new(0))),
+        };
+        if let Err(error) = bot.and_then(|mut bot| bot_controller::benchmark(&config, games, bot.as_mut())) {
+            eprintln!("{}", error);
+        }
+        return;
+    }
+
     init();
-    let mut input_controller = InputController::new();
+
+    let (mut config, mut errors) = config::load();
+    errors.extend(piece_set::init(&config.gameplay.pieces));
+    if !errors.is_empty() {
+        generic::error_print(&format!("Config problems, using defaults for these:\n{}", errors.join("\n")));
+        generic::clear_terminal();
+    }
+
+    let mut input_controller = InputController::new(&config);
+
+    if let Some(address) = spectate_address {
+        spectator_controller::spectate_loop(&address, &config, &mut input_controller);
+        cleanup_exit();
+        return;
+    }
+
+    if let Some(code) = fumen_code {
+        let final_fumen = match fumen::decode(&code, config.display.board_width, config.display.board_height) {
+            Ok(state) => new_game(&config, &mut input_controller, Some(&state)).map(|game| game.get_fumen()),
+            Err(error) => {
+                generic::error_print(&error);
+                None
+            },
+        };
+        cleanup_exit();
+        if let Some(Ok(code)) = final_fumen {
+            println!("Final position: {}", code);
+        }
+        return;
+    }
+
+    if let Some(command) = bot_command {
+        match ExternalBot::spawn(&command, &config, bot_controller::DEMO_ACTION_MS) {
+            Ok(mut bot) => {
+                let name = String::from(bot.get_name());
+                bot_controller::demo_loop(&config, &mut input_controller, &mut bot, &name);
+            },
+            Err(error) => generic::error_print(&error),
+        }
+        cleanup_exit();
+        return;
+    }
 
     loop {
         let selected_option = menu_controller::main_menu_loop(&mut input_controller);
 
         match selected_option {
-            MenuOption::New(_) => new_game(&mut input_controller),
-            MenuOption::Score(_) => menu_controller::score_menu_loop(&mut input_controller),
-            MenuOption::Quit(_) => break
+            MenuOption::Marathon => {
+                new_game(&config, &mut input_controller, None);
+            },
+            MenuOption::Fumen => {
+                if let Some(state) = menu_controller::fumen_menu_loop(&mut input_controller, &config) {
+                    new_game(&config, &mut input_controller, Some(&state));
+                }
+            },
+            MenuOption::Versus => versus_controller::versus_loop(&config, &mut input_controller),
+            MenuOption::Puzzle => puzzle_controller::puzzle_menu_loop(&config, &mut input_controller),
+            MenuOption::Finesse => finesse_controller::finesse_loop(&config, &mut input_controller),
+            MenuOption::Dig => dig_controller::dig_loop(&config, &mut input_controller),
+            MenuOption::AiDemo => bot_controller::demo_loop(&config, &mut input_controller, &mut Bot::new(bot_controller::DEMO_ACTION_MS), "AI demo"),
+            MenuOption::Host => net_controller::host_menu_loop(&mut input_controller, &mut config),
+            MenuOption::Join => net_controller::join_menu_loop(&mut input_controller, &mut config),
+            MenuOption::Score => menu_controller::score_menu_loop(&mut input_controller),
+            MenuOption::Stats => menu_controller::stats_menu_loop(&mut input_controller, "Lifetime statistics", &stats_controller::read_lifetime(), true),
+            MenuOption::Options => menu_controller::options_menu_loop(&mut input_controller, &mut config),
+            MenuOption::Controls => menu_controller::controls_menu_loop(&mut input_controller, &mut config),
+            MenuOption::Quit => break,
+            MenuOption::NewGame => (),
         }
     }
 
     cleanup_exit();
 }
 
+/// Value following `flag` in `args`, if the flag is given
+///
+/// Returns `Err` if the flag is given without a value
+fn flag_value(args: &[String], flag: &str) -> Result<Option<String>, ()> {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => args.get(index + 1).cloned().map(Some).ok_or(()),
+        None => Ok(None),
+    }
+}
+
 fn init() {
     generic::hide_cursor(true);
     generic::clear_terminal();
@@ -39,7 +177,30 @@ fn cleanup_exit() {
     generic::hide_cursor(false);
 }
 
-fn new_game(input_controller: &mut InputController) {
-    let mut game_controller = GameController::new();
-    game_controller.game_loop(input_controller);
+/// Play marathon, starting from board and piece of `setup` if given
+///
+/// Returns the finished game, or `None` if the setup couldn't be loaded
+fn new_game(config: &Config, input_controller: &mut InputController, setup: Option<&GameState>) -> Option<GameController> {
+    let mut publisher = match config.spectate.publish {
+        true => match Publisher::new(&config.spectate.address) {
+            Ok(publisher) => Some(publisher),
+            Err(error) => {
+                generic::error_print(&error);
+                None
+            },
+        },
+        false => None,
+    };
+
+    let mut game_controller = GameController::new(config);
+    if let Some(setup) = setup {
+        if let Err(error) = game_controller.load_state(setup) {
+            generic::clear_terminal();
+            generic::error_print(&error);
+            return None;
+        }
+    }
+    game_controller.game_loop(input_controller, publisher.as_mut());
+
+    Some(game_controller)
 }
@@ -0,0 +1,274 @@
+use crossterm::event::KeyCode;
+use crate::{generic, input_controller::InputController, text_field::TextField};
+
+const TITLE_Y: u16 = 1;
+const ITEMS_Y: u16 = 3;
+const CURSOR_X: u16 = 3;
+const LABEL_X: u16 = 5;
+const SLIDER_WIDTH: i32 = 10;
+
+pub enum MenuItemKind<T> {
+    /// Selecting returns item id from `Menu::run`
+    Button,
+    Submenu(Menu<T>),
+    Toggle(bool),
+    Slider { value: i32, min: i32, max: i32, step: i32 },
+    Choice { options: Vec<String>, index: usize },
+    Text(TextField),
+}
+
+pub struct MenuItem<T> {
+    pub id: T,
+    pub label: String,
+    /// Extra text shown right of a button
+    pub detail: String,
+    pub kind: MenuItemKind<T>,
+}
+
+pub enum MenuResult<T> {
+    Selected(T),
+    /// One of `Menu::extra_keys` was pressed on item
+    Key(T, KeyCode),
+    Back,
+}
+
+/// List of items navigated with arrow keys, ENTER and ESC
+pub struct Menu<T> {
+    pub title: String,
+    pub items: Vec<MenuItem<T>>,
+    /// Text shown below title
+    pub help: String,
+    /// Keys that make `run` return `MenuResult::Key`
+    pub extra_keys: Vec<KeyCode>,
+    cursor: usize,
+}
+
+impl<T> MenuItem<T> {
+    pub fn new(id: T, label: &str, kind: MenuItemKind<T>) -> MenuItem<T> {
+        MenuItem {
+            id,
+            label: String::from(label),
+            detail: String::new(),
+            kind,
+        }
+    }
+
+    pub fn get_bool(&self) -> bool {
+        matches!(self.kind, MenuItemKind::Toggle(true))
+    }
+
+    pub fn get_number(&self) -> i32 {
+        match self.kind {
+            MenuItemKind::Slider { value, .. } => value,
+            _ => 0,
+        }
+    }
+
+    pub fn get_choice(&self) -> &str {
+        match &self.kind {
+            MenuItemKind::Choice { options, index } => &options[*index],
+            _ => "",
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        match &self.kind {
+            MenuItemKind::Text(text_field) => text_field.get_value(),
+            _ => String::new(),
+        }
+    }
+
+    /// Change value by `direction` steps, returns true if changed
+    fn adjust(&mut self, direction: i32) -> bool {
+        match &mut self.kind {
+            MenuItemKind::Toggle(value) => {
+                *value = !*value;
+                true
+            },
+            MenuItemKind::Slider { value, min, max, step } => {
+                let new_value = (*value + direction * *step).clamp(*min, *max);
+                let changed = new_value != *value;
+                *value = new_value;
+                changed
+            },
+            MenuItemKind::Choice { options, index } => {
+                let len = options.len() as i32;
+                *index = ((*index as i32 + direction).rem_euclid(len)) as usize;
+                len > 1
+            },
+            _ => false,
+        }
+    }
+
+    fn value_str(&self) -> String {
+        match &self.kind {
+            MenuItemKind::Button => self.detail.clone(),
+            MenuItemKind::Submenu(_) => String::from(">"),
+            MenuItemKind::Toggle(value) => String::from(if *value { "[x]" } else { "[ ]" }),
+            MenuItemKind::Slider { value, min, max, .. } => {
+                let filled = if max > min { (value - min) * SLIDER_WIDTH / (max - min) } else { SLIDER_WIDTH };
+                format!(
+                    "[{}{}] {}",
+                    "#".repeat(filled as usize),
+                    "-".repeat((SLIDER_WIDTH - filled) as usize),
+                    value
+                )
+            },
+            MenuItemKind::Choice { options, index } => format!("< {} >", options[*index]),
+            MenuItemKind::Text(text_field) => text_field.get_visible(),
+        }
+    }
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new(title: &str, items: Vec<MenuItem<T>>) -> Menu<T> {
+        Menu {
+            title: String::from(title),
+            items,
+            help: String::new(),
+            extra_keys: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Show menu until a button is selected or ESC is pressed
+    ///
+    /// `on_change` is called every time a toggle, slider, choice or text is changed. A menu without items goes back right away
+    pub fn run(&mut self, input_controller: &mut InputController, on_change: &mut dyn FnMut(&MenuItem<T>)) -> MenuResult<T> {
+        if self.items.is_empty() {
+            return MenuResult::Back;
+        }
+
+        self.draw();
+
+        loop {
+            input_controller.update();
+
+            let prev_cursor = self.cursor;
+            if input_controller.key_pressed(KeyCode::Down) {
+                self.cursor = (self.cursor + 1) % self.items.len();
+            }
+            if input_controller.key_pressed(KeyCode::Up) {
+                self.cursor = (self.cursor + self.items.len() - 1) % self.items.len();
+            }
+            if prev_cursor != self.cursor {
+                self.draw_cursor(prev_cursor);
+            }
+
+            let direction = if input_controller.key_pressed(KeyCode::Left) {
+                -1
+            } else if input_controller.key_pressed(KeyCode::Right) {
+                1
+            } else {
+                0
+            };
+            if direction != 0 && self.items[self.cursor].adjust(direction) {
+                self.draw_item(self.cursor);
+                on_change(&self.items[self.cursor]);
+            }
+
+            if let Some(code) = self.extra_keys.iter().find(|code| input_controller.key_pressed(**code)) {
+                return MenuResult::Key(self.items[self.cursor].id, *code);
+            }
+
+            if input_controller.key_pressed(KeyCode::Esc) {
+                return MenuResult::Back;
+            }
+
+            if input_controller.key_pressed(KeyCode::Enter) {
+                if let Some(result) = self.select(input_controller, on_change) {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Handle ENTER on item under cursor
+    ///
+    /// Returns result if menu should stop running
+    fn select(&mut self, input_controller: &mut InputController, on_change: &mut dyn FnMut(&MenuItem<T>)) -> Option<MenuResult<T>> {
+        let value_x = self.value_x();
+        let item_y = self.items_y() + self.cursor as u16;
+        let item = &mut self.items[self.cursor];
+
+        match &mut item.kind {
+            MenuItemKind::Button => return Some(MenuResult::Selected(item.id)),
+            MenuItemKind::Submenu(submenu) => {
+                let result = submenu.run(input_controller, on_change);
+                if let MenuResult::Back = result {
+                    self.draw();
+                    return None;
+                }
+                return Some(result);
+            },
+            MenuItemKind::Text(text_field) => {
+                let prev_value = text_field.get_value();
+                if input_controller.read_text(text_field, value_x, item_y) {
+                    on_change(item);
+                } else {
+                    text_field.set_value(&prev_value);
+                }
+                self.draw_item(self.cursor);
+            },
+            MenuItemKind::Toggle(_) => {
+                item.adjust(1);
+                self.draw_item(self.cursor);
+                on_change(&self.items[self.cursor]);
+            },
+            _ => (),
+        }
+
+        None
+    }
+
+    pub fn draw(&self) {
+        generic::clear_terminal();
+
+        generic::move_cursor(CURSOR_X, TITLE_Y);
+        println!("{}", self.title);
+
+        if !self.help.is_empty() {
+            generic::move_cursor(CURSOR_X, TITLE_Y + 1);
+            println!("{}", self.help);
+        }
+
+        for i in 0..self.items.len() {
+            self.draw_item(i);
+        }
+        self.draw_cursor(self.cursor);
+    }
+
+    fn draw_item(&self, index: usize) {
+        let item = &self.items[index];
+        let y = self.items_y() + index as u16;
+
+        generic::move_cursor(LABEL_X, y);
+        print!("{}", item.label);
+        generic::move_cursor(self.value_x(), y);
+        print!("{:<40}", item.value_str());
+        generic::move_cursor(0, 0);
+    }
+
+    fn draw_cursor(&self, prev_index: usize) {
+        generic::move_cursor(CURSOR_X, self.items_y() + prev_index as u16);
+        print!(" ");
+        generic::move_cursor(CURSOR_X, self.items_y() + self.cursor as u16);
+        print!(">");
+        generic::move_cursor(0, 0);
+    }
+
+    /// First free row below items
+    pub fn get_bottom_y(&self) -> u16 {
+        self.items_y() + self.items.len() as u16 + 1
+    }
+
+    fn items_y(&self) -> u16 {
+        if self.help.is_empty() { ITEMS_Y } else { ITEMS_Y + 1 }
+    }
+
+    /// Column of values, right of longest label
+    fn value_x(&self) -> u16 {
+        let label_len = self.items.iter().map(|item| item.label.chars().count()).max().unwrap_or(0);
+        LABEL_X + label_len as u16 + 3
+    }
+}
@@ -1,65 +1,74 @@
 use crossterm::event::KeyCode;
 
-use crate::{input_controller::InputController, generic, highscore_controller};
+use crate::{input_controller::InputController, generic, highscore_controller, stats_controller::Stats, config::{self, Config}, action::Action, menu::{Menu, MenuItem, MenuItemKind, MenuResult}, tet, randomizer::RANDOMIZERS, theme::THEMES, text_field::TextField, game_state::GameState, fumen};
 
-#[derive(Clone)]
+/// Longest fumen code that can be entered, which is enough for a page of any board
+const FUMEN_MAX_LEN: usize = 2000;
+/// Columns of the fumen code shown while entering it, so long codes scroll instead of wrapping
+const FUMEN_VIEW_WIDTH: usize = 40;
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum MenuOption {
-    New(String),
-    Score(String),
-    Quit(String),
+    NewGame,
+    Marathon,
+    Versus,
+    Host,
+    Join,
+    AiDemo,
+    Finesse,
+    Fumen,
+    Puzzle,
+    Dig,
+    Score,
+    Stats,
+    Options,
+    Controls,
+    Quit,
 }
 
-pub fn main_menu_loop(input_controller: &mut InputController) -> MenuOption {
-    generic::clear_terminal();
-
-    let menu_options: [MenuOption; 3] = [
-        MenuOption::New(String::from("New Game")),
-        MenuOption::Score(String::from("View Highscores")),
-        MenuOption::Quit(String::from("Quit")),
-    ];
+#[derive(Clone, Copy, PartialEq)]
+enum Setting {
+    StartLevel,
+    Rotation,
+    Randomizer,
+    PreviewCount,
+    Big,
+    PartialLockOut,
+    Das,
+    Arr,
+    Theme,
+    Ghost,
+    Publish,
+    DigRows,
+    DigMessiness,
+    DigRise,
+}
 
-    generic::move_cursor(3, 1);
-    println!("Rust Terminal Tetris");
-
-    let menu_options_offset = 3;
-    update_cursor(0, 0, menu_options_offset);
-
-    for (i, option) in menu_options.iter().enumerate() {
-        generic::move_cursor(5, menu_options_offset + i as u16);
-        println!("{}", match option {
-            MenuOption::New(label) => label,
-            MenuOption::Score(label) => label,
-            MenuOption::Quit(label) => label,
-        });
-    }
+pub fn main_menu_loop(input_controller: &mut InputController) -> MenuOption {
+    let mode_menu = Menu::new("Select mode", vec![
+        MenuItem::new(MenuOption::Marathon, "Marathon", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Versus, "Versus (2 players)", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Host, "Host online versus", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Join, "Join online versus", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Puzzle, "Puzzles", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Dig, "Dig race", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Finesse, "Finesse trainer", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Fumen, "Load fumen", MenuItemKind::Button),
+        MenuItem::new(MenuOption::AiDemo, "AI demo", MenuItemKind::Button),
+    ]);
 
-    let mut cursor_index: u16 = 0;
+    let mut menu = Menu::new("Rust Terminal Tetris", vec![
+        MenuItem::new(MenuOption::NewGame, "New Game", MenuItemKind::Submenu(mode_menu)),
+        MenuItem::new(MenuOption::Score, "View Highscores", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Stats, "View Statistics", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Options, "Options", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Controls, "Controls", MenuItemKind::Button),
+        MenuItem::new(MenuOption::Quit, "Quit", MenuItemKind::Button),
+    ]);
 
     loop {
-        input_controller.update();
-
-        let prev_cursor_index = cursor_index;
-        if input_controller.key_pressed(KeyCode::Down) {
-            if cursor_index + 1 == menu_options.len() as u16 {
-                cursor_index = 0
-            } else {
-                cursor_index += 1;
-            }
-        }
-        if input_controller.key_pressed(KeyCode::Up) {
-            if cursor_index == 0 {
-                cursor_index = menu_options.len() as u16 - 1;
-            } else {
-                cursor_index -= 1;
-            }
-        }
-
-        if prev_cursor_index != cursor_index {
-            update_cursor(prev_cursor_index, cursor_index, menu_options_offset);
-        }
-
-        if input_controller.key_pressed(KeyCode::Enter) {
-            return menu_options[cursor_index as usize].clone();
+        if let MenuResult::Selected(option) = menu.run(input_controller, &mut |_| {}) {
+            return option;
         }
     }
 }
@@ -88,9 +97,188 @@ pub fn score_menu_loop(input_controller: &mut InputController) {
     }
 }
 
-fn update_cursor(prev_y: u16, y: u16, offset: u16) {
-    generic::move_cursor(3, prev_y + offset);
-    println!(" ");
-    generic::move_cursor(3, y + offset);
-    println!(">");
+/// Show `stats`, with the number of games played if they're `lifetime` stats of all games
+pub fn stats_menu_loop(input_controller: &mut InputController, title: &str, stats: &Stats, lifetime: bool) {
+    generic::clear_terminal();
+
+    generic::move_cursor(3, 1);
+    println!("{}", title);
+
+    if lifetime {
+        generic::move_cursor(3, 3);
+        println!("Games played:     {}", stats.games);
+        stats.print(3, 4);
+    } else {
+        stats.print(3, 3);
+    }
+
+    loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) || input_controller.key_pressed(KeyCode::Enter) {
+            return;
+        }
+    }
+}
+
+/// Let user change gameplay and display settings
+///
+/// Changes are saved to config file right away
+pub fn options_menu_loop(input_controller: &mut InputController, config: &mut Config) {
+    let choice = |options: &[&str], value: &str| MenuItemKind::Choice {
+        options: options.iter().map(|option| String::from(*option)).collect(),
+        index: options.iter().position(|option| *option == value).unwrap_or(0),
+    };
+    let theme_names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+    let repeat = config.get_repeat(Action::MoveLeft).cloned().unwrap_or_default();
+
+    let mut menu = Menu::new("Options", vec![
+        MenuItem::new(Setting::StartLevel, "Starting level", MenuItemKind::Slider { value: config.gameplay.start_level as i32, min: 1, max: 30, step: 1 }),
+        MenuItem::new(Setting::Rotation, "Rotation system", choice(&tet::ROTATION_SYSTEMS, &config.gameplay.rotation)),
+        MenuItem::new(Setting::Randomizer, "Randomizer", choice(&RANDOMIZERS, &config.gameplay.randomizer)),
+        MenuItem::new(Setting::PreviewCount, "Preview count", MenuItemKind::Slider { value: config.gameplay.preview_count as i32, min: 0, max: 6, step: 1 }),
+        MenuItem::new(Setting::Big, "Big mode", MenuItemKind::Toggle(config.gameplay.big)),
+        MenuItem::new(Setting::PartialLockOut, "Partial lock out", MenuItemKind::Toggle(config.gameplay.partial_lock_out)),
+        MenuItem::new(Setting::Das, "DAS (ms)", MenuItemKind::Slider { value: repeat.delay_ms as i32, min: 0, max: 500, step: 10 }),
+        MenuItem::new(Setting::Arr, "ARR (ms)", MenuItemKind::Slider { value: repeat.interval_ms as i32, min: 0, max: 200, step: 5 }),
+        MenuItem::new(Setting::Theme, "Theme", choice(&theme_names, &config.display.theme)),
+        MenuItem::new(Setting::Ghost, "Ghost piece", MenuItemKind::Toggle(config.display.ghost)),
+        MenuItem::new(Setting::Publish, "Publish for spectators", MenuItemKind::Toggle(config.spectate.publish)),
+        MenuItem::new(Setting::DigRows, "Dig rows", MenuItemKind::Slider { value: config.dig.rows as i32, min: 1, max: config.display.board_height as i32 - 4, step: 1 }),
+        MenuItem::new(Setting::DigMessiness, "Dig messiness (%)", MenuItemKind::Slider { value: config.dig.messiness as i32, min: 0, max: 100, step: 10 }),
+        MenuItem::new(Setting::DigRise, "Dig rise (s, 0: off)", MenuItemKind::Slider { value: config.dig.rise_secs as i32, min: 0, max: 60, step: 1 }),
+    ]);
+    menu.help = String::from("LEFT/RIGHT: Change   ESC: Back");
+    if !config.allows_big() {
+        menu.items.retain(|item| item.id != Setting::Big);
+    }
+
+    menu.run(input_controller, &mut |item| {
+        set_option(config, item);
+
+        if let Err(error) = config::write(config) {
+            generic::error_print(&error);
+        }
+    });
+
+    // Use new DAS and ARR
+    input_controller.apply_config(config);
+}
+
+fn set_option(config: &mut Config, item: &MenuItem<Setting>) {
+    match item.id {
+        Setting::StartLevel => config.gameplay.start_level = item.get_number() as u8,
+        Setting::Rotation => config.gameplay.rotation = String::from(item.get_choice()),
+        Setting::Randomizer => config.gameplay.randomizer = String::from(item.get_choice()),
+        Setting::PreviewCount => config.gameplay.preview_count = item.get_number() as u8,
+        Setting::Big => config.gameplay.big = item.get_bool(),
+        Setting::PartialLockOut => config.gameplay.partial_lock_out = item.get_bool(),
+        Setting::Das => {
+            for action in [Action::MoveLeft, Action::MoveRight] {
+                config.get_repeat_mut(action).delay_ms = item.get_number() as u16;
+            }
+        },
+        Setting::Arr => {
+            for action in [Action::MoveLeft, Action::MoveRight] {
+                config.get_repeat_mut(action).interval_ms = item.get_number() as u16;
+            }
+        },
+        Setting::Theme => config.display.theme = String::from(item.get_choice()),
+        Setting::Ghost => config.display.ghost = item.get_bool(),
+        Setting::Publish => config.spectate.publish = item.get_bool(),
+        Setting::DigRows => config.dig.rows = item.get_number() as u16,
+        Setting::DigMessiness => config.dig.messiness = item.get_number() as u8,
+        Setting::DigRise => config.dig.rise_secs = item.get_number() as u16,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FumenOption {
+    Code,
+    Start,
+}
+
+/// Ask for a fumen code, until one is decoded into a board or ESC is pressed
+pub fn fumen_menu_loop(input_controller: &mut InputController, config: &Config) -> Option<GameState> {
+    let mut code_field = TextField::new(FUMEN_MAX_LEN, |c| c.is_ascii_graphic());
+    code_field.set_view_width(FUMEN_VIEW_WIDTH);
+
+    let mut menu = Menu::new("Load fumen", vec![
+        MenuItem::new(FumenOption::Code, "Code", MenuItemKind::Text(code_field)),
+        MenuItem::new(FumenOption::Start, "Start", MenuItemKind::Button),
+    ]);
+    menu.help = String::from("ENTER: Edit or select   ESC: Back");
+
+    loop {
+        if !matches!(menu.run(input_controller, &mut |_| ()), MenuResult::Selected(FumenOption::Start)) {
+            return None;
+        }
+
+        match fumen::decode(&menu.items[0].get_text(), config.display.board_width, config.display.board_height) {
+            Ok(state) => return Some(state),
+            Err(error) => {
+                generic::clear_terminal();
+                generic::error_print(&error);
+            },
+        }
+    }
+}
+
+/// List actions with their keys, and let user add or clear keys
+///
+/// Changes are used right away and saved to config file
+pub fn controls_menu_loop(input_controller: &mut InputController, config: &mut Config) {
+    let items = Action::ALL.iter().map(|action| MenuItem::new(*action, action.label(), MenuItemKind::Button)).collect();
+    let mut menu = Menu::new("Controls", items);
+    menu.help = String::from("ENTER: Add key   BACKSPACE: Remove last key   DELETE: Clear keys   ESC: Back");
+    menu.extra_keys = vec![KeyCode::Backspace, KeyCode::Delete];
+    update_controls(&mut menu, config);
+
+    loop {
+        match menu.run(input_controller, &mut |_| {}) {
+            MenuResult::Selected(action) => {
+                generic::move_cursor(3, menu.get_bottom_y());
+                print!("Press key to add to \"{}\", or ESC to cancel", action.label());
+
+                let key = loop {
+                    input_controller.update();
+
+                    if let Some(key) = input_controller.pressed_keys().first() {
+                        break *key;
+                    }
+                };
+
+                if !key.same_code(KeyCode::Esc) {
+                    config.keys.bind(action, key);
+                    save_controls(input_controller, config);
+                }
+            },
+            MenuResult::Key(action, KeyCode::Backspace) => {
+                config.keys.get_mut(action).pop();
+                save_controls(input_controller, config);
+            },
+            MenuResult::Key(action, _) => {
+                config.keys.get_mut(action).clear();
+                save_controls(input_controller, config);
+            },
+            MenuResult::Back => return,
+        }
+
+        update_controls(&mut menu, config);
+    }
+}
+
+fn save_controls(input_controller: &mut InputController, config: &Config) {
+    input_controller.apply_config(config);
+
+    if let Err(error) = config::write(config) {
+        generic::error_print(&error);
+    }
+}
+
+/// Show keys of each action
+fn update_controls(menu: &mut Menu<Action>, config: &Config) {
+    for item in &mut menu.items {
+        item.detail = config.keys.get(item.id).join(", ");
+    }
 }
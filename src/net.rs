@@ -0,0 +1,235 @@
+use std::{io::{BufRead, BufReader, Write}, net::{TcpListener, TcpStream, ToSocketAddrs}, sync::mpsc::{self, Receiver, TryRecvError}, thread, time::{Duration, SystemTime}};
+use serde::{Deserialize, Serialize};
+use crate::{game_state::GameState, config::{Config, GameplayConfig}, piece_set};
+
+/// Increased when messages or fixed rules like garbage change, so different versions don't play each other
+pub const PROTOCOL_VERSION: u32 = 3;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Message between two games, sent as one line of JSON
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// First message from both sides
+    Hello { version: u32 },
+    /// Sent by host after hello, with rules both games use
+    Start { seed: u64, rules: Rules },
+    /// Garbage lines sent to opponent
+    Garbage { lines: u16 },
+    State(GameState),
+    /// Sender topped out or quit
+    GameOver,
+}
+
+/// Settings of the host that decide how the game plays, which the joining game uses too
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rules {
+    pub gameplay: GameplayConfig,
+    pub board_width: u16,
+    pub board_height: u16,
+    /// Text of the piece set file of the host, since each game loads its own set at start
+    pub piece_set: String,
+}
+
+impl Rules {
+    pub fn new(config: &Config) -> Rules {
+        Rules {
+            gameplay: config.gameplay.clone(),
+            board_width: config.display.board_width,
+            board_height: config.display.board_height,
+            piece_set: String::from(piece_set::get().get_source()),
+        }
+    }
+
+    /// Copy of `config` using these rules, or an error if they aren't valid settings or use other pieces
+    pub fn apply(self, config: &Config) -> Result<Config, String> {
+        if self.piece_set != piece_set::get().get_source() {
+            return Err(format!("Opponent plays with a different piece set \"{}\", use the same set file to play them", self.gameplay.pieces));
+        }
+
+        let mut config = config.clone();
+        // Same pieces can be in a file of another name
+        let pieces = config.gameplay.pieces;
+        config.gameplay = self.gameplay;
+        config.gameplay.pieces = pieces;
+        config.display.board_width = self.board_width;
+        config.display.board_height = self.board_height;
+
+        let errors = config.validate();
+        if !errors.is_empty() {
+            return Err(format!("Opponent's rules are invalid:\n{}", errors.join("\n")));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Line based connection, reading messages on a separate thread so receiving never blocks
+pub struct Connection {
+    stream: TcpStream,
+    receiver: Receiver<Result<Message, String>>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Connection, String> {
+        stream.set_nodelay(true).map_err(|error| error.to_string())?;
+        let reader = stream.try_clone().map_err(|error| error.to_string())?;
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let message = match line {
+                    Ok(line) => serde_json::from_str(&line).map_err(|error| format!("Invalid message: {}", error)),
+                    Err(_) => break,
+                };
+
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+
+            let _ = sender.send(Err(String::from("Connection lost")));
+        });
+
+        Ok(Connection { stream, receiver })
+    }
+
+    /// Accept a connection on `listener`, or return `None` if nobody connected yet
+    pub fn accept(listener: &TcpListener) -> Result<Option<Connection>, String> {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false).map_err(|error| error.to_string())?;
+                Ok(Some(Connection::new(stream)?))
+            },
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    pub fn connect(address: &str) -> Result<Connection, String> {
+        let address = address.to_socket_addrs()
+            .map_err(|_| format!("Invalid address \"{}\"", address))?
+            .next()
+            .ok_or(format!("Invalid address \"{}\"", address))?;
+
+        let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+            .map_err(|error| format!("Failed to connect to {}: {}", address, error))?;
+        Connection::new(stream)
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), String> {
+        let mut line = serde_json::to_string(message).map_err(|error| error.to_string())?;
+        line.push('\n');
+        self.stream.write_all(line.as_bytes()).map_err(|_| String::from("Connection lost"))
+    }
+
+    /// Next received message, if any
+    pub fn receive(&mut self) -> Result<Option<Message>, String> {
+        match self.receiver.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(String::from("Connection lost")),
+        }
+    }
+
+    /// Wait for next message, giving up after handshake timeout
+    pub fn receive_wait(&mut self) -> Result<Message, String> {
+        let start = SystemTime::now();
+        while start.elapsed().unwrap() < HANDSHAKE_TIMEOUT {
+            if let Some(message) = self.receive()? {
+                return Ok(message);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Err(String::from("Opponent didn't respond"))
+    }
+
+    /// Exchange hello messages and check that both sides use the same protocol version
+    pub fn handshake(&mut self) -> Result<(), String> {
+        self.send(&Message::Hello { version: PROTOCOL_VERSION })?;
+
+        match self.receive_wait()? {
+            Message::Hello { version } if version == PROTOCOL_VERSION => Ok(()),
+            Message::Hello { version } => Err(format!("Opponent uses protocol version {}, but this game uses {}", version, PROTOCOL_VERSION)),
+            _ => Err(String::from("Opponent didn't say hello")),
+        }
+    }
+
+    /// Send rules of `config` with a new seed to the joining game
+    ///
+    /// Returns the seed both games use
+    pub fn send_start(&mut self, config: &Config) -> Result<u64, String> {
+        let seed = rand::random();
+        self.send(&Message::Start { seed, rules: Rules::new(config) })?;
+        Ok(seed)
+    }
+
+    /// Wait for rules and seed of the host
+    ///
+    /// Returns `config` with the rules of the host, and the seed
+    pub fn receive_start(&mut self, config: &Config) -> Result<(Config, u64), String> {
+        let Message::Start { seed, rules } = self.receive_wait()? else {
+            return Err(String::from("Opponent didn't start the game"));
+        };
+
+        Ok((rules.apply(config)?, seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joining_game_uses_rules_of_host() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let mut host_config = Config::default();
+        host_config.gameplay.initial_fall_ms = 300;
+        host_config.gameplay.preview_count = 5;
+        host_config.gameplay.start_level = 7;
+        host_config.gameplay.big = true;
+        host_config.gameplay.rotation = String::from("srs");
+        host_config.display.board_width = 12;
+
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut connection = Connection::new(stream).unwrap();
+            connection.handshake().unwrap();
+            connection.send_start(&host_config).unwrap()
+        });
+
+        let mut connection = Connection::connect(&address).unwrap();
+        connection.handshake().unwrap();
+        let (config, seed) = connection.receive_start(&Config::default()).unwrap();
+
+        assert_eq!(seed, host.join().unwrap());
+        assert_eq!(config.gameplay.initial_fall_ms, 300);
+        assert_eq!(config.gameplay.preview_count, 5);
+        assert_eq!(config.gameplay.start_level, 7);
+        assert!(config.gameplay.big);
+        assert_eq!(config.gameplay.rotation, "srs");
+        assert_eq!(config.display.board_width, 12);
+    }
+
+    #[test]
+    fn invalid_rules_are_refused() {
+        let mut config = Config::default();
+        config.gameplay.start_level = 200;
+
+        assert!(Rules::new(&config).apply(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn other_piece_set_is_refused() {
+        let mut rules = Rules::new(&Config::default());
+        rules.gameplay.pieces = String::from("trominoes");
+        rules.piece_set.push_str("\n[[piece]]\nname = \"X\"\ncells = [[0, 0]]\npivot = [0, 0]\nspawn = [0, -1]\nrotations = 1\n");
+
+        let error = rules.apply(&Config::default()).err().unwrap();
+        assert!(error.contains("different piece set"));
+    }
+}
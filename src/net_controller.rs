@@ -0,0 +1,177 @@
+use std::{net::TcpListener, time::SystemTime};
+use crossterm::event::KeyCode;
+use crate::{generic, config::{self, Config}, input_controller::InputController, game_controller::GameController, action::Action, menu::{Menu, MenuItem, MenuItemKind, MenuResult}, net::{Connection, Message}, remote_view::RemoteView, text_field::TextField, versus_controller};
+
+/// Time between each board state sent to opponent
+const STATE_INTERVAL_MS: u128 = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+enum NetOption {
+    Port,
+    Address,
+    Start,
+}
+
+/// Ask for port, then wait for an opponent to join and play
+pub fn host_menu_loop(input_controller: &mut InputController, config: &mut Config) {
+    let mut port_field = TextField::new(5, |c| c.is_ascii_digit());
+    port_field.set_value(&config.net.port.to_string());
+
+    let mut menu = Menu::new("Host online versus", vec![
+        MenuItem::new(NetOption::Port, "Port", MenuItemKind::Text(port_field)),
+        MenuItem::new(NetOption::Start, "Start hosting", MenuItemKind::Button),
+    ]);
+
+    if !run_net_menu(&mut menu, input_controller, config) {
+        return;
+    }
+
+    if let Err(error) = host(input_controller, config) {
+        generic::clear_terminal();
+        generic::error_print(&error);
+    }
+}
+
+/// Ask for address of host, then connect and play
+pub fn join_menu_loop(input_controller: &mut InputController, config: &mut Config) {
+    let mut address_field = TextField::new(64, |c| c.is_ascii_graphic());
+    address_field.set_value(&config.net.address);
+
+    let mut menu = Menu::new("Join online versus", vec![
+        MenuItem::new(NetOption::Address, "Address", MenuItemKind::Text(address_field)),
+        MenuItem::new(NetOption::Start, "Connect", MenuItemKind::Button),
+    ]);
+
+    if !run_net_menu(&mut menu, input_controller, config) {
+        return;
+    }
+
+    if let Err(error) = join(input_controller, config) {
+        generic::clear_terminal();
+        generic::error_print(&error);
+    }
+}
+
+/// Show menu, saving port and address to config file when changed
+///
+/// Returns true if start was selected
+fn run_net_menu(menu: &mut Menu<NetOption>, input_controller: &mut InputController, config: &mut Config) -> bool {
+    menu.help = String::from("ENTER: Edit or select   ESC: Back");
+
+    let result = menu.run(input_controller, &mut |item| {
+        match item.id {
+            NetOption::Port => config.net.port = item.get_text().parse().unwrap_or(config.net.port),
+            NetOption::Address => config.net.address = item.get_text(),
+            NetOption::Start => return,
+        }
+
+        if let Err(error) = config::write(config) {
+            generic::error_print(&error);
+        }
+    });
+
+    matches!(result, MenuResult::Selected(NetOption::Start))
+}
+
+fn host(input_controller: &mut InputController, config: &Config) -> Result<(), String> {
+    let listener = TcpListener::bind(("0.0.0.0", config.net.port))
+        .map_err(|error| format!("Failed to host on port {}: {}", config.net.port, error))?;
+    listener.set_nonblocking(true).map_err(|error| error.to_string())?;
+
+    generic::clear_terminal();
+    generic::move_cursor(3, 1);
+    print!("Waiting for opponent on port {}   ESC: Cancel", config.net.port);
+    generic::move_cursor(0, 0);
+
+    let mut connection = loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) {
+            return Ok(());
+        }
+
+        if let Some(connection) = Connection::accept(&listener)? {
+            break connection;
+        }
+    };
+
+    connection.handshake()?;
+    let seed = connection.send_start(config)?;
+
+    play(input_controller, config, connection, seed)
+}
+
+fn join(input_controller: &mut InputController, config: &Config) -> Result<(), String> {
+    generic::clear_terminal();
+    generic::move_cursor(3, 1);
+    print!("Connecting to {}", config.net.address);
+    generic::move_cursor(0, 0);
+
+    let mut connection = Connection::connect(&config.net.address)?;
+    connection.handshake()?;
+
+    // Use rules of host, so both games are the same
+    let (config, seed) = connection.receive_start(config)?;
+
+    play(input_controller, &config, connection, seed)
+}
+
+/// Play against opponent until one tops out or quits
+fn play(input_controller: &mut InputController, config: &Config, mut connection: Connection, seed: u64) -> Result<(), String> {
+    generic::clear_terminal();
+    let (layout, opponent_layout) = versus_controller::get_layouts(config)?;
+    let mut game = GameController::new_at(config, layout, seed);
+    let mut opponent = RemoteView::new(config, opponent_layout);
+    let mut state_time: Option<SystemTime> = None;
+
+    let won = 'game: loop {
+        input_controller.update();
+
+        if input_controller.action_pressed(Action::Quit) {
+            connection.send(&Message::GameOver)?;
+            return Ok(());
+        }
+
+        while let Some(message) = connection.receive()? {
+            match message {
+                Message::Garbage { lines } => game.add_garbage(lines),
+                Message::State(state) => opponent.draw(&state),
+                Message::GameOver => break 'game true,
+                Message::Hello { .. } | Message::Start { .. } => (),
+            }
+        }
+
+        game.count_inputs(&input_controller.pressed_actions(0));
+        let actions = input_controller.player_actions(0);
+        let alive = game.step(&actions);
+
+        let garbage = game.take_sent_garbage();
+        if garbage > 0 {
+            connection.send(&Message::Garbage { lines: garbage })?;
+        }
+
+        if !alive || state_time.is_none_or(|time| time.elapsed().unwrap().as_millis() >= STATE_INTERVAL_MS) {
+            connection.send(&Message::State(game.get_state()))?;
+            state_time = Some(SystemTime::now());
+        }
+
+        if !alive {
+            connection.send(&Message::GameOver)?;
+            break false;
+        }
+
+        input_controller.end_update();
+    };
+
+    if won {
+        game.print_message(2, "You win!");
+        opponent.print_message(2, "Topped out");
+    } else {
+        game.print_message(2, "Topped out");
+        opponent.print_message(2, "Opponent wins");
+    }
+    game.print_message(4, "Press ENTER");
+    InputController::wait_for_enter();
+
+    Ok(())
+}
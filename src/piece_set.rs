@@ -0,0 +1,210 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::OnceLock};
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+use crate::{config, game_state::{EMPTY_CELL, GARBAGE_CELL}, point::Point, tet::{Tet, TetKind}};
+
+const DIRECTORY: &str = "pieces";
+/// Used when no piece set file is given, so the game runs without the pieces folder
+const STANDARD: &str = include_str!("../pieces/standard.toml");
+
+/// Piece set used for the rest of the run
+static PIECE_SET: OnceLock<PieceSet> = OnceLock::new();
+
+/// Pieces that can be played, and the SRS kick tables they use
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PieceSet {
+    /// Text of the set file, which tells if two games play with the same pieces
+    #[serde(skip)]
+    source: String,
+    /// Kick tables by name, with 2 rows per rotation: turning clockwise, then counterclockwise
+    ///
+    /// Kicks are (x, y) with y pointing up, tried in order until one fits
+    #[serde(default)]
+    kicks: BTreeMap<String, Vec<Vec<(i16, i16)>>>,
+    #[serde(rename = "piece")]
+    pieces: Vec<PieceDef>,
+}
+
+/// Shape and rotation of a piece, in its spawn rotation
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PieceDef {
+    /// Single character, which is also used for the piece's blocks in game states
+    pub name: String,
+    /// Blocks as (x, y) with y pointing down
+    pub cells: Vec<(i16, i16)>,
+    /// Point the piece rotates around, and which is placed at the piece position
+    pub pivot: (i16, i16),
+    /// Position at spawn, where x is from the column left of the board center
+    pub spawn: (i16, i16),
+    /// Color name like "dark_yellow" or "#rrggbb", or the theme color by order in the set if not set
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Rotations of classic rotation, or 1 if the piece doesn't rotate
+    pub rotations: u8,
+    /// Turn of classic rotation away from the spawn rotation is clockwise on screen
+    #[serde(default = "default_true")]
+    pub rotate_clockwise: bool,
+    /// Name of SRS kick table, or no kicks if not set
+    #[serde(default)]
+    pub kicks: Option<String>,
+    /// Rotation states of SRS, used instead of turning `cells` when SRS is selected
+    #[serde(default)]
+    pub srs: Option<SrsDef>,
+}
+
+/// Piece in each rotation state of SRS, with its own spawn orientation and rotation center
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SrsDef {
+    /// Blocks as (x, y) with y pointing down, for spawn rotation and then each clockwise turn
+    pub states: Vec<Vec<(i16, i16)>>,
+    /// Point the states are placed around, which stays in place when turning
+    pub pivot: (i16, i16),
+    /// Position at spawn, like `spawn` of the piece
+    pub spawn: (i16, i16),
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl PieceSet {
+    /// Built-in set of the 7 tetrominoes
+    pub fn standard() -> PieceSet {
+        let mut set: PieceSet = toml::from_str(STANDARD).expect("Built-in piece set is valid");
+        set.source = String::from(STANDARD);
+        set
+    }
+
+    /// Load set `name`, either "standard" or a file in the pieces folder without its extension
+    pub fn load(name: &str) -> Result<PieceSet, Vec<String>> {
+        if name == "standard" {
+            return Ok(PieceSet::standard());
+        }
+
+        let path = Path::new(DIRECTORY).join(format!("{}.toml", name));
+        let file_name = path.display();
+        let set_str = fs::read_to_string(&path).map_err(|_| vec![format!("Failed to read piece set file {}", file_name)])?;
+        let mut set: PieceSet = toml::from_str(&set_str).map_err(|error| vec![format!("Invalid piece set file {}:\n{}", file_name, error)])?;
+        set.source = set_str;
+
+        let errors: Vec<String> = set.validate().iter().map(|error| format!("{}: {}", file_name, error)).collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(set)
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.pieces.is_empty() || self.pieces.len() > u8::MAX as usize {
+            errors.push(format!("Piece set must have between 1 and {} pieces", u8::MAX));
+        }
+
+        for (name, table) in &self.kicks {
+            if table.len() != 8 || table.iter().any(|row| row.is_empty()) {
+                errors.push(format!("Kick table \"{}\" must have 8 rows of at least one kick", name));
+            }
+        }
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let mut chars = piece.name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(EMPTY_CELL | GARBAGE_CELL), None) | (None, _) | (_, Some(_)) => {
+                    errors.push(format!("Piece name \"{}\" must be a single character other than \"{}\" and \"{}\"", piece.name, EMPTY_CELL, GARBAGE_CELL));
+                },
+                _ => (),
+            }
+            if self.pieces[..i].iter().any(|other| other.name == piece.name) {
+                errors.push(format!("Piece name \"{}\" is used more than once", piece.name));
+            }
+            if piece.cells.is_empty() {
+                errors.push(format!("Piece \"{}\" has no cells", piece.name));
+            }
+            if !(1..=4).contains(&piece.rotations) {
+                errors.push(format!("Piece \"{}\" must have between 1 and 4 rotations", piece.name));
+            }
+            if piece.color.as_deref().is_some_and(|color| config::parse_color(color).is_none()) {
+                errors.push(format!("Piece \"{}\" has unknown color \"{}\"", piece.name, piece.color.as_deref().unwrap_or_default()));
+            }
+            if let Some(kicks) = piece.kicks.as_ref().filter(|kicks| !self.kicks.contains_key(*kicks)) {
+                errors.push(format!("Piece \"{}\" uses unknown kick table \"{}\"", piece.name, kicks));
+            }
+            if piece.srs.as_ref().is_some_and(|srs| srs.states.len() != 4 || srs.states.iter().any(|state| state.len() != piece.cells.len())) {
+                errors.push(format!("Piece \"{}\" must have 4 SRS states with as many cells as the piece", piece.name));
+            }
+        }
+
+        errors
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_pieces(&self) -> &[PieceDef] {
+        &self.pieces
+    }
+
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub fn get_piece(&self, kind: TetKind) -> &PieceDef {
+        &self.pieces[kind.index()]
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<TetKind> {
+        self.pieces.iter().position(|piece| piece.name == name).map(|index| TetKind::new(index as u8))
+    }
+
+    /// Color set by the piece definition, if any
+    pub fn get_color(&self, kind: TetKind) -> Option<Color> {
+        self.get_piece(kind).color.as_deref().and_then(config::parse_color)
+    }
+
+    /// Columns and rows of the largest preview of any piece
+    pub fn get_preview_size(&self) -> (u16, u16) {
+        let mut size = (0, 0);
+        for piece in &self.pieces {
+            let model: Vec<Point> = piece.cells.iter().map(|(x, y)| Point::new(*x, *y)).collect();
+            let points = Tet::lie_flat(&model);
+            size.0 = size.0.max(points.iter().map(|point| point.x as u16 + 1).max().unwrap_or(0));
+            size.1 = size.1.max(points.iter().map(|point| point.y as u16 + 1).max().unwrap_or(0));
+        }
+
+        size
+    }
+
+    /// Kicks to try when turning from rotation `flips`, or only staying in place if the piece has no kick table
+    pub fn get_kicks(&self, kind: TetKind, flips: u8, clockwise: bool) -> &[(i16, i16)] {
+        let table = self.get_piece(kind).kicks.as_ref().and_then(|name| self.kicks.get(name));
+        match table {
+            Some(table) => &table[flips as usize * 2 + if clockwise { 0 } else { 1 }],
+            None => &[(0, 0)],
+        }
+    }
+}
+
+/// Use `name` as piece set for the rest of the run
+///
+/// Returns any problems found, in which case the standard set is used
+pub fn init(name: &str) -> Vec<String> {
+    let (set, errors) = match PieceSet::load(name) {
+        Ok(set) => (set, Vec::new()),
+        Err(errors) => (PieceSet::standard(), errors),
+    };
+
+    // Only fails if pieces were already used, which then keep their set
+    let _ = PIECE_SET.set(set);
+    errors
+}
+
+/// Piece set of this run, the standard set unless `init` was called first
+pub fn get() -> &'static PieceSet {
+    PIECE_SET.get_or_init(PieceSet::standard)
+}
@@ -1,25 +1,21 @@
 use crossterm::style::Color;
-
-pub const X_WIDTH: i16 = 2;
+use crate::tet::TetKind;
 
 pub struct Point {
     pub x: i16,
     pub y: i16,
     pub color: Color,
+    /// Kind of piece this point is a block of, if any
+    pub kind: Option<TetKind>,
 }
 
 impl Point {
     pub fn new(x: i16, y: i16) -> Point {
-        Point { x, y, color: Color::White, }
-    }
-
-    pub fn new_with_color(x: i16, y: i16, color: Color) -> Point {
-        Point { x, y, color, }
+        Point { x, y, color: Color::White, kind: None, }
     }
 
-    /// x multiplied by X_WIDTH
-    pub fn x_width(&self) -> i16 {
-        self.x * self::X_WIDTH
+    pub fn new_block(x: i16, y: i16, color: Color, kind: TetKind) -> Point {
+        Point { x, y, color, kind: Some(kind), }
     }
 }
 
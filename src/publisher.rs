@@ -0,0 +1,64 @@
+use std::{io::Write, net::{TcpListener, TcpStream}, time::SystemTime};
+use crate::{game_controller::GameController, net::{Message, PROTOCOL_VERSION}};
+
+/// Time between each game state sent to spectators
+const STATE_INTERVAL_MS: u128 = 100;
+
+/// Sends live game states to spectators, as newline-delimited JSON messages
+///
+/// Spectators get a hello message when connecting, then state messages, then a game over message
+pub struct Publisher {
+    listener: TcpListener,
+    spectators: Vec<TcpStream>,
+    state_time: Option<SystemTime>,
+}
+
+impl Publisher {
+    pub fn new(address: &str) -> Result<Publisher, String> {
+        let listener = TcpListener::bind(address).map_err(|error| format!("Failed to publish on {}: {}", address, error))?;
+        listener.set_nonblocking(true).map_err(|error| error.to_string())?;
+
+        Ok(Publisher {
+            listener,
+            spectators: Vec::new(),
+            state_time: None,
+        })
+    }
+
+    /// Accept new spectators, and send state of `game` if it's time to
+    pub fn update(&mut self, game: &GameController) {
+        while let Ok((mut stream, _)) = self.listener.accept() {
+            // Spectators that can't keep up are dropped, rather than slowing down the game
+            let hello = Message::Hello { version: PROTOCOL_VERSION };
+            if stream.set_nonblocking(true).is_ok() && Publisher::send_to(&mut stream, &hello) {
+                self.spectators.push(stream);
+                // Send state right away to new spectator
+                self.state_time = None;
+            }
+        }
+
+        if !self.spectators.is_empty() && self.state_time.is_none_or(|time| time.elapsed().unwrap().as_millis() >= STATE_INTERVAL_MS) {
+            self.send(&Message::State(game.get_state()));
+            self.state_time = Some(SystemTime::now());
+        }
+    }
+
+    pub fn game_over(&mut self, game: &GameController) {
+        self.send(&Message::State(game.get_state()));
+        self.send(&Message::GameOver);
+    }
+
+    fn send(&mut self, message: &Message) {
+        self.spectators.retain_mut(|stream| Publisher::send_to(stream, message));
+    }
+
+    /// Returns false if spectator should be dropped
+    fn send_to(stream: &mut TcpStream, message: &Message) -> bool {
+        let Ok(mut line) = serde_json::to_string(message) else {
+            return true;
+        };
+        line.push('\n');
+
+        stream.write_all(line.as_bytes()).is_ok()
+    }
+}
@@ -0,0 +1,164 @@
+use std::{fs, path::Path};
+use serde::Deserialize;
+use crate::{config, game_state::{EMPTY_CELL, GARBAGE_CELL}, tet::TetKind};
+
+/// Folder with puzzle packs, one TOML file per pack
+pub const DIRECTORY: &str = "puzzles";
+
+/// Named collection of puzzles, loaded from one file
+#[derive(Deserialize)]
+pub struct PuzzlePack {
+    pub name: String,
+    #[serde(rename = "puzzle")]
+    pub puzzles: Vec<Puzzle>,
+}
+
+/// Starting board, fixed Tet sequence and goal to reach by the end of the sequence
+#[derive(Deserialize, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    /// Bottom rows of the board, from top to bottom, with a piece name like "T" for blocks, "G" for garbage and "." for empty cells
+    #[serde(default)]
+    pub board: Vec<String>,
+    /// Names of Tets to play in order, like "IOT"
+    pub pieces: String,
+    pub goal: Goal,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    /// Clear at least this many lines
+    Lines(u32),
+    /// Leave the board empty
+    PerfectClear,
+}
+
+impl Goal {
+    pub fn label(&self) -> String {
+        match self {
+            Goal::Lines(1) => String::from("Clear 1 line"),
+            Goal::Lines(lines) => format!("Clear {} lines", lines),
+            Goal::PerfectClear => String::from("Perfect clear"),
+        }
+    }
+}
+
+impl Puzzle {
+    /// Board width used by the puzzle, or `None` if it has no board
+    pub fn get_width(&self) -> Option<u16> {
+        self.board.first().map(|row| row.chars().count() as u16)
+    }
+
+    /// Occupied cells as (x, y, kind) on a board `height` rows high, where no kind is garbage
+    pub fn get_cells(&self, height: u16) -> Vec<(i16, i16, Option<TetKind>)> {
+        let top = height as i16 - self.board.len() as i16;
+
+        self.board.iter().enumerate()
+            .flat_map(|(y, row)| row.chars().enumerate().map(move |(x, cell)| (x as i16, top + y as i16, cell)))
+            .filter(|(_, _, cell)| *cell != EMPTY_CELL)
+            .map(|(x, y, cell)| (x, y, TetKind::by_name(&cell.to_string())))
+            .collect()
+    }
+
+    pub fn get_pieces(&self) -> Vec<TetKind> {
+        self.pieces.chars().filter_map(|name| TetKind::by_name(&name.to_string())).collect()
+    }
+
+    /// Problems that make the puzzle unplayable on a board `height` rows high
+    fn validate(&self, height: u16) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.pieces.is_empty() {
+            errors.push(format!("Puzzle \"{}\" has no pieces", self.name));
+        }
+        if let Some(name) = self.pieces.chars().find(|name| TetKind::by_name(&name.to_string()).is_none()) {
+            errors.push(format!("Puzzle \"{}\" has unknown piece \"{}\"", self.name, name));
+        }
+
+        if self.board.len() > height as usize {
+            errors.push(format!("Puzzle \"{}\" has {} rows, but the board is {} high", self.name, self.board.len(), height));
+        }
+        let width = self.get_width().unwrap_or(0);
+        if self.board.iter().any(|row| row.chars().count() as u16 != width) {
+            errors.push(format!("Puzzle \"{}\" has rows of different widths", self.name));
+        }
+        if width > 0 && !(config::MIN_BOARD_WIDTH..=config::MAX_BOARD_WIDTH).contains(&width) {
+            errors.push(format!(
+                "Puzzle \"{}\" must be between {} and {} wide, but is {}",
+                self.name,
+                config::MIN_BOARD_WIDTH,
+                config::MAX_BOARD_WIDTH,
+                width
+            ));
+        }
+        let is_valid_cell = |cell: char| cell == EMPTY_CELL || cell == GARBAGE_CELL || TetKind::by_name(&cell.to_string()).is_some();
+        if let Some(cell) = self.board.iter().flat_map(|row| row.chars()).find(|cell| !is_valid_cell(*cell)) {
+            errors.push(format!("Puzzle \"{}\" has unknown cell \"{}\"", self.name, cell));
+        }
+
+        errors
+    }
+}
+
+/// Load every pack in the puzzle folder, sorted by file name
+///
+/// Returns packs and any problems found. Packs with problems are left out
+pub fn load_packs(height: u16) -> (Vec<PuzzlePack>, Vec<String>) {
+    let mut paths: Vec<_> = match fs::read_dir(DIRECTORY) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .collect(),
+        Err(_) => return (Vec::new(), vec![format!("No puzzle folder \"{}\" found", DIRECTORY)]),
+    };
+    paths.sort();
+
+    let mut packs = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match load_pack(&path, height) {
+            Ok(pack) => packs.push(pack),
+            Err(pack_errors) => errors.extend(pack_errors),
+        }
+    }
+
+    (packs, errors)
+}
+
+fn load_pack(path: &Path, height: u16) -> Result<PuzzlePack, Vec<String>> {
+    let file_name = path.display();
+    let pack_str = fs::read_to_string(path).map_err(|_| vec![format!("Failed to read puzzle file {}", file_name)])?;
+    let pack: PuzzlePack = toml::from_str(&pack_str).map_err(|error| vec![format!("Invalid puzzle file {}:\n{}", file_name, error)])?;
+
+    if pack.puzzles.is_empty() {
+        return Err(vec![format!("{}: Puzzle pack \"{}\" has no puzzles", file_name, pack.name)]);
+    }
+
+    let errors: Vec<String> = pack.puzzles.iter()
+        .flat_map(|puzzle| puzzle.validate(height))
+        .map(|error| format!("{}: {}", file_name, error))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(pack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_without_puzzles_is_refused() {
+        let path = std::env::temp_dir().join("tetris_empty_pack.toml");
+        fs::write(&path, "name = \"Empty\"\npuzzle = []\n").unwrap();
+
+        let errors = load_pack(&path, 20).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("has no puzzles"));
+    }
+}
@@ -0,0 +1,104 @@
+use crossterm::event::KeyCode;
+use crate::{generic, config::Config, game_controller::GameController, input_controller::InputController, action::Action, layout::Layout, menu::{Menu, MenuItem, MenuItemKind, MenuResult}, puzzle::{self, Goal, Puzzle, PuzzlePack}};
+
+const MODE: &str = "puzzle";
+/// Width of message area, so shorter messages clear longer ones
+const MESSAGE_WIDTH: usize = 20;
+
+/// Browse puzzle packs, playing selected puzzles until ESC is pressed
+pub fn puzzle_menu_loop(config: &Config, input_controller: &mut InputController) {
+    generic::clear_terminal();
+    let (packs, errors) = puzzle::load_packs(config.display.board_height);
+    if !errors.is_empty() {
+        generic::error_print(&format!("Puzzle problems:\n{}", errors.join("\n")));
+        generic::clear_terminal();
+    }
+    if packs.is_empty() {
+        generic::error_print(&format!("No puzzles found in folder \"{}\"", puzzle::DIRECTORY));
+        return;
+    }
+
+    let items = packs.iter().enumerate()
+        .map(|(index, pack)| {
+            let mut item = MenuItem::new(index, &pack.name, MenuItemKind::Button);
+            item.detail = format!("{} puzzles", pack.puzzles.len());
+            item
+        })
+        .collect();
+    let mut menu = Menu::new("Puzzle packs", items);
+
+    while let MenuResult::Selected(index) = menu.run(input_controller, &mut |_| ()) {
+        pack_menu_loop(config, input_controller, &packs[index]);
+    }
+}
+
+fn pack_menu_loop(config: &Config, input_controller: &mut InputController, pack: &PuzzlePack) {
+    let items = pack.puzzles.iter().enumerate()
+        .map(|(index, puzzle)| {
+            let mut item = MenuItem::new(index, &puzzle.name, MenuItemKind::Button);
+            item.detail = puzzle.goal.label();
+            item
+        })
+        .collect();
+    let mut menu = Menu::new(&pack.name, items);
+
+    while let MenuResult::Selected(index) = menu.run(input_controller, &mut |_| ()) {
+        play(config, input_controller, &pack.puzzles[index]);
+    }
+}
+
+/// Play `puzzle` until its sequence runs out, then show if the goal was reached
+fn play(config: &Config, input_controller: &mut InputController, puzzle: &Puzzle) {
+    let mut config = config.clone();
+    if let Some(width) = puzzle.get_width() {
+        config.display.board_width = width;
+        config.gameplay.big &= config.allows_big();
+    }
+    let pieces = puzzle.get_pieces();
+
+    generic::clear_terminal();
+    let mut game = GameController::new_at(&config, Layout::new(&config, MODE), rand::random());
+    game.set_occupied(&puzzle.get_cells(config.display.board_height));
+    game.set_sequence(&pieces);
+    game.print_message(0, &pad(&puzzle.name));
+    game.print_message(1, &pad(&puzzle.goal.label()));
+    game.print_message(4, "ESC: Back");
+
+    loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) || input_controller.action_pressed(Action::Quit) {
+            return;
+        }
+
+        if input_controller.action_pressed(Action::Pause) && !GameController::pause(std::slice::from_mut(&mut game), input_controller) {
+            return;
+        }
+
+        game.count_inputs(&input_controller.pressed_actions(0));
+        let actions = input_controller.player_actions(0);
+        if !game.step(&actions) {
+            break;
+        }
+
+        input_controller.end_update();
+    }
+
+    game.finish_animations();
+
+    // Topping out ends the game before the last Tet is placed. A held Tet can't be placed once the sequence runs out, so it counts as used
+    let used = game.get_stats().pieces as usize + game.get_hold().map_or(0, |_| 1);
+    let placed_all = used == pieces.len();
+    let solved = placed_all && match puzzle.goal {
+        Goal::Lines(lines) => game.get_stats().lines >= lines,
+        Goal::PerfectClear => game.occupied.is_empty(),
+    };
+
+    game.print_message(3, &pad(if solved { "Solved!" } else { "Failed" }));
+    game.print_message(4, &pad("Press ENTER"));
+    InputController::wait_for_enter();
+}
+
+fn pad(text: &str) -> String {
+    format!("{:width$.width$}", text, width = MESSAGE_WIDTH)
+}
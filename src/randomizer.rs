@@ -0,0 +1,38 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use crate::{config::Config, tet::TetKind};
+
+pub const RANDOMIZERS: [&str; 2] = ["random", "bag"];
+
+/// Picks upcoming Tets
+///
+/// Randomizers with the same seed and config give the same Tets
+pub struct Randomizer {
+    rng: StdRng,
+    /// Deal every kind of the piece set in shuffled order before repeating any
+    use_bag: bool,
+    bag: Vec<TetKind>,
+}
+
+impl Randomizer {
+    pub fn new(config: &Config, seed: u64) -> Randomizer {
+        Randomizer {
+            rng: StdRng::seed_from_u64(seed),
+            use_bag: config.gameplay.randomizer == "bag",
+            bag: Vec::new(),
+        }
+    }
+
+    pub fn next(&mut self) -> TetKind {
+        if !self.use_bag {
+            let kinds = TetKind::all();
+            return kinds[self.rng.gen_range(0..kinds.len())];
+        }
+
+        if self.bag.is_empty() {
+            self.bag = TetKind::all();
+            self.bag.shuffle(&mut self.rng);
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
@@ -0,0 +1,124 @@
+use crossterm::style::Color;
+use crate::{board::{Board, GARBAGE_COLOR}, config::Config, game_state::{GameState, EMPTY_CELL, GARBAGE_CELL}, generic, layout::{Layout, PanelKind}, tet::TetKind, piece_set};
+
+/// Read-only view of a game played elsewhere, drawn from its game states
+pub struct RemoteView {
+    board: Board,
+    layout: Layout,
+    preview_count: usize,
+    /// Cells currently drawn, so only changed cells are redrawn
+    cells: Vec<Vec<char>>,
+    /// Last drawn state, so only changed panels are redrawn
+    last_state: Option<GameState>,
+}
+
+impl RemoteView {
+    pub fn new(config: &Config, layout: Layout) -> RemoteView {
+        let board = Board::new(config, layout.get_board_x(), layout.get_board_y());
+        board.draw_frame();
+        board.clear_board();
+
+        RemoteView {
+            cells: vec![vec![EMPTY_CELL; board.get_width() as usize]; board.get_height() as usize],
+            board,
+            layout,
+            preview_count: config.gameplay.preview_count as usize,
+            last_state: None,
+        }
+    }
+
+    pub fn draw(&mut self, state: &GameState) {
+        let cells = state.get_cells();
+        for (y, row) in cells.iter().enumerate().take(self.board.get_height() as usize) {
+            for (x, cell) in row.iter().enumerate().take(self.board.get_width() as usize) {
+                if self.cells[y][x] != *cell {
+                    self.draw_cell(x as i16, y as i16, *cell);
+                    self.cells[y][x] = *cell;
+                }
+            }
+        }
+        generic::set_color(Color::Reset);
+
+        for kind in [PanelKind::Score, PanelKind::Level, PanelKind::Lines, PanelKind::Garbage, PanelKind::Hold, PanelKind::Next] {
+            let changed = match &self.last_state {
+                Some(last) => RemoteView::panel_changed(kind, state, last),
+                None => true,
+            };
+
+            if changed {
+                self.print_panel(kind, state);
+            }
+        }
+
+        self.last_state = Some(state.clone());
+        generic::move_cursor(0, 0);
+    }
+
+    /// Print `text` at `line` of the message area
+    pub fn print_message(&self, line: u16, text: &str) {
+        let (message_x, message_y) = self.layout.get_message(line);
+        generic::move_cursor(message_x, message_y);
+        print!("{}", text);
+        generic::move_cursor(0, 0);
+    }
+
+    fn draw_cell(&self, x: i16, y: i16, cell: char) {
+        if cell == GARBAGE_CELL {
+            generic::set_color(GARBAGE_COLOR);
+            self.board.print_cell(x, y, self.board.get_block_str(None));
+            return;
+        }
+
+        match TetKind::by_name(&cell.to_string()) {
+            Some(kind) => {
+                generic::set_color(self.board.get_color(kind));
+                self.board.print_cell(x, y, self.board.get_block_str(Some(kind)));
+            },
+            None => self.board.print_cell(x, y, &self.board.get_empty_str()),
+        }
+    }
+
+    fn panel_changed(kind: PanelKind, state: &GameState, last: &GameState) -> bool {
+        match kind {
+            PanelKind::Score => state.score != last.score,
+            PanelKind::Level => state.level != last.level,
+            PanelKind::Lines => state.lines != last.lines,
+            PanelKind::Garbage => state.incoming_garbage != last.incoming_garbage,
+            PanelKind::Hold => state.hold != last.hold,
+            PanelKind::Next => state.queue != last.queue,
+            PanelKind::Timer | PanelKind::Stats | PanelKind::GarbageLeft => false,
+        }
+    }
+
+    /// Print panel if it's part of the layout, leaving out panels the game state doesn't have
+    fn print_panel(&self, kind: PanelKind, state: &GameState) {
+        let Some((x, y)) = self.layout.get_panel(kind) else {
+            return;
+        };
+
+        generic::move_cursor(x, y);
+        match kind {
+            PanelKind::Score => print!("Score: {:?}", state.score),
+            PanelKind::Level => print!("Level: {:?}", state.level),
+            PanelKind::Lines => print!("Lines: {:?}", state.lines),
+            PanelKind::Garbage => {
+                print!("Garbage: ");
+                generic::set_color(Color::Red);
+                print!("{:<7}", "#".repeat((state.incoming_garbage as usize).min(7)));
+                generic::set_color(Color::Reset);
+            },
+            PanelKind::Hold => {
+                print!("Hold");
+                self.board.print_preview(state.get_hold(), x, y + 1);
+            },
+            PanelKind::Next => {
+                print!("Next");
+                let queue = state.get_queue();
+                for i in 0..self.preview_count {
+                    self.board.print_preview(queue.get(i).copied(), x, y + 1 + i as u16 * (piece_set::get().get_preview_size().1 + 1));
+                }
+            },
+            PanelKind::Timer | PanelKind::Stats | PanelKind::GarbageLeft => (),
+        }
+    }
+}
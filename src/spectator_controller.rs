@@ -0,0 +1,67 @@
+use crossterm::event::KeyCode;
+use crate::{generic, config::Config, input_controller::InputController, layout::Layout, net::{Connection, Message, PROTOCOL_VERSION}, remote_view::RemoteView};
+
+const MODE: &str = "spectate";
+
+/// Watch game published at `address` without taking input, until it ends or ESC is pressed
+pub fn spectate_loop(address: &str, config: &Config, input_controller: &mut InputController) {
+    if let Err(error) = spectate(address, config, input_controller) {
+        generic::clear_terminal();
+        generic::error_print(&error);
+    }
+}
+
+fn spectate(address: &str, config: &Config, input_controller: &mut InputController) -> Result<(), String> {
+    generic::clear_terminal();
+    generic::move_cursor(3, 1);
+    print!("Connecting to {}", address);
+    generic::move_cursor(0, 0);
+
+    let mut connection = Connection::connect(address)?;
+    match connection.receive_wait()? {
+        Message::Hello { version } if version == PROTOCOL_VERSION => (),
+        Message::Hello { version } => return Err(format!("Game uses protocol version {}, but this game uses {}", version, PROTOCOL_VERSION)),
+        _ => return Err(format!("{} is not a game stream", address)),
+    }
+
+    // Board size comes from first state
+    let state = loop {
+        if let Message::State(state) = connection.receive_wait()? {
+            break state;
+        }
+    };
+    let width = state.board.first().map(|row| row.chars().count()).unwrap_or(0);
+    if width == 0 {
+        return Err(String::from("Game stream has an empty board"));
+    }
+
+    let mut config = config.clone();
+    config.display.board_width = width as u16;
+    config.display.board_height = state.board.len() as u16;
+
+    generic::clear_terminal();
+    let mut view = RemoteView::new(&config, Layout::new(&config, MODE));
+    view.draw(&state);
+    view.print_message(4, "Spectating   ESC: Stop");
+
+    loop {
+        input_controller.update();
+
+        if input_controller.key_pressed(KeyCode::Esc) {
+            return Ok(());
+        }
+
+        while let Some(message) = connection.receive()? {
+            match message {
+                Message::State(state) => view.draw(&state),
+                Message::GameOver => {
+                    view.print_message(2, "Game over");
+                    view.print_message(4, "Press ENTER           ");
+                    InputController::wait_for_enter();
+                    return Ok(());
+                },
+                _ => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{generic, tet::TetKind};
+
+const FILE: &str = "stats.txt";
+const CLEAR_NAMES: [&str; 4] = ["Single", "Double", "Triple", "Tetris"];
+
+/// Statistics for a single game, or totals across games
+#[derive(Clone, Default)]
+pub struct Stats {
+    pub games: u32,
+    pub duration_ms: u64,
+    pub lines: u32,
+    pub pieces: u32,
+    pub inputs: u32,
+    /// Pieces placed by piece name
+    pub pieces_by_kind: BTreeMap<String, u32>,
+    /// Count of singles, doubles, triples, and tetrises
+    pub clears: [u32; 4],
+    /// Longest streak of pieces in a row that cleared lines
+    pub max_combo: u32,
+    combo: u32,
+}
+
+impl Stats {
+    pub fn new_game() -> Stats {
+        Stats { games: 1, ..Default::default() }
+    }
+
+    /// Register a placed piece and how many rows it cleared
+    pub fn add_piece(&mut self, kind: TetKind, cleared_rows: u8) {
+        self.pieces += 1;
+        *self.pieces_by_kind.entry(String::from(kind.name())).or_insert(0) += 1;
+
+        if cleared_rows == 0 {
+            self.combo = 0;
+            return;
+        }
+
+        self.lines += cleared_rows as u32;
+        self.clears[(cleared_rows.min(4) - 1) as usize] += 1;
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo);
+    }
+
+    pub fn add_inputs(&mut self, count: u32) {
+        self.inputs += count;
+    }
+
+    /// Add totals of `other` to `self`
+    pub fn add(&mut self, other: &Stats) {
+        self.games += other.games;
+        self.duration_ms += other.duration_ms;
+        self.lines += other.lines;
+        self.pieces += other.pieces;
+        self.inputs += other.inputs;
+        for (name, count) in &other.pieces_by_kind {
+            *self.pieces_by_kind.entry(name.clone()).or_insert(0) += count;
+        }
+        for i in 0..self.clears.len() {
+            self.clears[i] += other.clears[i];
+        }
+        self.max_combo = self.max_combo.max(other.max_combo);
+    }
+
+    pub fn pieces_per_second(&self) -> f32 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+
+        self.pieces as f32 / (self.duration_ms as f32 / 1000.0)
+    }
+
+    pub fn inputs_per_piece(&self) -> f32 {
+        if self.pieces == 0 {
+            return 0.0;
+        }
+
+        self.inputs as f32 / self.pieces as f32
+    }
+
+    /// Print stats as lines starting at given position
+    pub fn print(&self, x: u16, y: u16) {
+        let seconds = self.duration_ms / 1000;
+        let mut lines: Vec<String> = vec![
+            format!("Time:             {}:{:02}", seconds / 60, seconds % 60),
+            format!("Lines cleared:    {}", self.lines),
+            format!("Pieces placed:    {}", self.pieces),
+            format!("Pieces / second:  {:.2}", self.pieces_per_second()),
+            format!("Inputs / piece:   {:.2}", self.inputs_per_piece()),
+            format!("Max combo:        {}", self.max_combo),
+            String::new(),
+        ];
+
+        for (i, name) in CLEAR_NAMES.iter().enumerate() {
+            lines.push(format!("{:<18}{}", format!("{}:", name), self.clears[i]));
+        }
+        lines.push(String::new());
+
+        for kind in TetKind::all() {
+            lines.push(format!("{:<18}{}", format!("{} pieces:", kind.name()), self.pieces_by_kind.get(kind.name()).unwrap_or(&0)));
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            generic::move_cursor(x, y + i as u16);
+            println!("{}", line);
+        }
+    }
+}
+
+/// Add stats of a finished game to lifetime stats file
+pub fn add_to_lifetime(stats: &Stats) {
+    let mut lifetime = read_lifetime();
+    lifetime.add(stats);
+    write_lifetime(&lifetime);
+}
+
+pub fn write_lifetime(stats: &Stats) {
+    let mut stats_str = String::new();
+    stats_str.push_str(&format!("games:{};", stats.games));
+    stats_str.push_str(&format!("duration_ms:{};", stats.duration_ms));
+    stats_str.push_str(&format!("lines:{};", stats.lines));
+    stats_str.push_str(&format!("pieces:{};", stats.pieces));
+    stats_str.push_str(&format!("inputs:{};", stats.inputs));
+    stats_str.push_str(&format!("max_combo:{};", stats.max_combo));
+    for (i, name) in CLEAR_NAMES.iter().enumerate() {
+        stats_str.push_str(&format!("{}:{};", name.to_lowercase(), stats.clears[i]));
+    }
+    for (name, count) in &stats.pieces_by_kind {
+        stats_str.push_str(&format!("piece_{}:{};", name, count));
+    }
+
+    if fs::write(FILE, stats_str).is_err() {
+        generic::error_print(&format!("Failed to write to stats file {}", FILE));
+    }
+}
+
+pub fn read_lifetime() -> Stats {
+    if !Path::new(FILE).exists() {
+        return Stats::default();
+    }
+
+    if let Ok(stats_str) = fs::read_to_string(FILE) {
+        return parse_stats_str(&stats_str);
+    } else {
+        generic::error_print(&format!("Failed to read from stats file {}", FILE));
+    }
+
+    Stats::default()
+}
+
+/// Parse `key:value;` pairs, ignoring unknown keys
+pub fn parse_stats_str(stats_str: &str) -> Stats {
+    let mut stats = Stats::default();
+
+    for pair_str in stats_str.split(';') {
+        if !pair_str.contains(':') {
+            continue;
+        }
+
+        let mut split = pair_str.split(':');
+        let key = split.next().unwrap();
+        let value: u64 = split.next().unwrap().parse().unwrap_or(0);
+
+        match key {
+            "games" => stats.games = value as u32,
+            "duration_ms" => stats.duration_ms = value,
+            "lines" => stats.lines = value as u32,
+            "pieces" => stats.pieces = value as u32,
+            "inputs" => stats.inputs = value as u32,
+            "max_combo" => stats.max_combo = value as u32,
+            _ => {
+                if let Some(i) = CLEAR_NAMES.iter().position(|name| name.to_lowercase() == key) {
+                    stats.clears[i] = value as u32;
+                } else if let Some(name) = key.strip_prefix("piece_") {
+                    stats.pieces_by_kind.insert(String::from(name), value as u32);
+                }
+            }
+        }
+    }
+
+    stats
+}
@@ -1,327 +1,420 @@
 use crossterm::style::Color;
-use rand::Rng;
-use crate::{point::Point, generic, game_controller::GameController, board::Board};
+use crate::{point::Point, generic, game_controller::GameController, board::Board, piece_set};
 
+pub const ROTATION_SYSTEMS: [&str; 2] = ["classic", "srs"];
+
+/// Piece kind, by order in the piece set
+#[derive(Clone, Copy, PartialEq)]
+pub struct TetKind(u8);
+
+impl TetKind {
+    pub fn new(index: u8) -> TetKind {
+        TetKind(index)
+    }
+
+    /// Every kind of the piece set
+    pub fn all() -> Vec<TetKind> {
+        (0..piece_set::get().len()).map(|index| TetKind(index as u8)).collect()
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn by_name(name: &str) -> Option<TetKind> {
+        piece_set::get().by_name(name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        &piece_set::get().get_piece(*self).name
+    }
+}
+
+/// Outcome of moving a Tet down
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fall {
+    Moved,
+    /// Couldn't move, so it was placed
+    Placed,
+    /// Locked out when placed, or pushed blocks off the board by garbage that rose after placing
+    ToppedOut,
+}
+
+#[derive(Clone)]
 pub struct Tet {
+    pub kind: TetKind,
     pub pos: Point,
     pivot: Point,
-    model: [Point; 4],
+    model: Vec<Point>,
     color: Color,
     allowed_flips: u8,
     flips: u8,
     rotate_clockwise: bool,
+    /// Last successful move was a rotation
+    rotated_last: bool,
+    /// Board cells per side of each model cell, 2 in big mode
+    scale: i16,
 }
 
 impl Tet {
-    fn new_i() -> Tet {
-        Tet {
-            pos: Point::new(4, -3),
-            pivot: Point::new(0, 1),
-            model: [
-                Point::new(0, 0),
-                Point::new(0, 1),
-                Point::new(0, 2),
-                Point::new(0, 3),
-            ],
-            color: Color::Blue,
-            allowed_flips: 1,
-            flips: 0,
-            rotate_clockwise: true,
+    /// Get Tet of `kind` at its spawn position, colored and sized by config
+    ///
+    /// With SRS, pieces that define SRS states spawn in the SRS spawn state
+    pub fn new(kind: TetKind, game_controller: &GameController) -> Tet {
+        let mut tet = Tet::of_kind(kind);
+        if let Some(srs) = piece_set::get().get_piece(kind).srs.as_ref().filter(|_| game_controller.config.gameplay.rotation == "srs") {
+            tet.model = Tet::state_model(&srs.states[0]);
+            tet.pivot = Point::new(srs.pivot.0, srs.pivot.1);
+            tet.pos = Point::new(srs.spawn.0, srs.spawn.1);
         }
+        tet.scale = if game_controller.config.gameplay.big { 2 } else { 1 };
+        // Spawn positions put the bottom of the piece right above the board, which scaling keeps
+        tet.pos.x = tet.pos.x * tet.scale + game_controller.board.get_width() as i16 / 2 - 1;
+        tet.pos.y *= tet.scale;
+        // Big pieces stay on a grid of whole cells counted from the left wall and the floor, so they can reach both walls and land flush
+        tet.pos.x -= tet.pos.x.rem_euclid(tet.scale);
+        tet.pos.y -= (tet.pos.y - game_controller.board.get_height() as i16).rem_euclid(tet.scale);
+        tet.color = game_controller.config.piece_color(tet.kind);
+        tet
     }
 
-    fn new_l() -> Tet {
-        Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(0, 1),
-            model: [
-                Point::new(0, 0),
-                Point::new(0, 1),
-                Point::new(0, 2),
-                Point::new(1, 2),
-            ],
-            color: Color::Red,
-            allowed_flips: 3,
-            flips: 0,
-            rotate_clockwise: true,
-        }
-    }
+    /// Get Tet of `kind` in spawn rotation, with spawn position relative to the board center, and default color
+    pub fn of_kind(kind: TetKind) -> Tet {
+        let piece = piece_set::get().get_piece(kind);
 
-    fn new_j() -> Tet {
         Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(0, 1),
-            model: [
-                Point::new(0, 0),
-                Point::new(0, 1),
-                Point::new(0, 2),
-                Point::new(-1, 2),
-            ],
-            color: Color::Green,
-            allowed_flips: 3,
+            kind,
+            pos: Point::new(piece.spawn.0, piece.spawn.1),
+            pivot: Point::new(piece.pivot.0, piece.pivot.1),
+            model: piece.cells.iter().map(|(x, y)| Point::new(*x, *y)).collect(),
+            color: piece_set::get().get_color(kind).unwrap_or(Color::White),
+            allowed_flips: piece.rotations - 1,
             flips: 0,
-            rotate_clockwise: true,
+            rotate_clockwise: piece.rotate_clockwise,
+            rotated_last: false,
+            scale: 1,
         }
     }
 
-    fn new_t() -> Tet {
-        Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(1, 0),
-            model: [
-                Point::new(0, 0),
-                Point::new(1, 0),
-                Point::new(1, 1),
-                Point::new(2, 0),
-            ],
-            color: Color::Yellow,
-            allowed_flips: 3,
-            flips: 0,
-            rotate_clockwise: true,
+    /// Get board position of individual points in model, with `scale` by `scale` points per model point
+    pub fn points_pos(&self) -> Vec<Point> {
+        let mut points = Vec::new();
+        for point in &self.model {
+            let x = self.pos.x + (point.x - self.pivot.x) * self.scale;
+            let y = self.pos.y + (point.y - self.pivot.y) * self.scale;
+            for offset_y in 0..self.scale {
+                for offset_x in 0..self.scale {
+                    points.push(Point::new_block(x + offset_x, y + offset_y, self.color, self.kind));
+                }
+            }
         }
+
+        points
     }
 
-    fn new_o() -> Tet {
-        Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(0, 0),
-            model: [
-                Point::new(0, 0),
-                Point::new(1, 0),
-                Point::new(0, 1),
-                Point::new(1, 1),
-            ],
-            color: Color::Magenta,
-            allowed_flips: 0,
-            flips: 0,
-            rotate_clockwise: true,
-        }
+    /// Cells moved by each move sideways or down, so big pieces move and land on whole model cells
+    pub fn get_step(&self) -> i16 {
+        self.scale
     }
 
-    fn new_s() -> Tet {
-        Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(1, 1),
-            model: [
-                Point::new(0, 1),
-                Point::new(1, 1),
-                Point::new(1, 0),
-                Point::new(2, 0),
-            ],
-            color: Color::Cyan,
-            allowed_flips: 1,
-            flips: 0,
-            rotate_clockwise: false,
-        }
+    /// Lock out: Tet locked entirely above the visible board, or partly if `partial` lock out is on
+    pub fn is_locked_out(&self, partial: bool) -> bool {
+        let points = self.points_pos();
+        let hidden = points.iter().filter(|point| point.y < 0).count();
+
+        hidden == points.len() || (partial && hidden > 0)
     }
 
-    fn new_z() -> Tet {
-        Tet {
-            pos: Point::new(4, -2),
-            pivot: Point::new(1, 1),
-            model: [
-                Point::new(2, 1),
-                Point::new(1, 1),
-                Point::new(1, 0),
-                Point::new(0, 0),
-            ],
-            color: Color::DarkYellow,
-            allowed_flips: 1,
-            flips: 0,
-            rotate_clockwise: true,
+    /// Place Tet, return false if it locked out
+    pub fn place(&self, game_controller: &mut GameController) -> bool {
+        if self.is_locked_out(game_controller.config.gameplay.partial_lock_out) {
+            return false;
         }
+
+        game_controller.occupied.add_points(&self.points_pos());
+
+        true
     }
 
-    pub fn new_random() -> Tet {
-        let rnd: u8 = rand::thread_rng().gen_range(0..=6);
-        match rnd {
-            0 => return Tet::new_i(),
-            1 => return Tet::new_l(),
-            2 => return Tet::new_j(),
-            3 => return Tet::new_t(),
-            4 => return Tet::new_o(),
-            5 => return Tet::new_s(),
-            _ => return Tet::new_z(),
-        }
+    /// Model points moved to start at 0, 0, and turned to lie flat so they fit in as few rows as possible
+    pub fn preview_points(&self) -> Vec<Point> {
+        Tet::lie_flat(&self.model)
     }
 
-    /// Get board position of individual points in model
-    pub fn points_pos(&self) -> [Point; 4] {
-        return [
-            Point::new_with_color(
-                self.pos.x - self.pivot.x + self.model[0].x,
-                self.pos.y - self.pivot.y + self.model[0].y,
-                self.color,
-            ),
-            Point::new_with_color(
-                self.pos.x - self.pivot.x + self.model[1].x,
-                self.pos.y - self.pivot.y + self.model[1].y,
-                self.color,
-            ),
-            Point::new_with_color(
-                self.pos.x - self.pivot.x + self.model[2].x,
-                self.pos.y - self.pivot.y + self.model[2].y,
-                self.color,
-            ),
-            Point::new_with_color(
-                self.pos.x - self.pivot.x + self.model[3].x,
-                self.pos.y - self.pivot.y + self.model[3].y,
-                self.color,
-            ),
-        ];
-    }
-
-    pub fn can_place(&self) -> bool {
-        for point in self.points_pos() {
-            if point.y < 0 {
-                return false;
+    /// `model` moved to start at 0, 0, and turned to lie flat if it's taller than wide
+    pub fn lie_flat(model: &[Point]) -> Vec<Point> {
+        let mut points = model.to_vec();
+
+        let span = |values: Vec<i16>| values.iter().max().unwrap() - values.iter().min().unwrap() + 1;
+        let width = span(points.iter().map(|point| point.x).collect());
+        let height = span(points.iter().map(|point| point.y).collect());
+        if height > width {
+            for point in &mut points {
+                (point.x, point.y) = (-point.y, point.x);
             }
         }
 
-        return true;
-    }
-
-    /// Place Tet, return success
-    pub fn place(&self, occupied: &mut Vec<Point>) -> bool {
-        if !self.can_place() {
-            return false;
+        let min_x = points.iter().map(|point| point.x).min().unwrap();
+        let min_y = points.iter().map(|point| point.y).min().unwrap();
+        for point in &mut points {
+            point.x -= min_x;
+            point.y -= min_y;
         }
 
-        let points = self.points_pos();
-        occupied.append(&mut points.to_vec());
-
-        return true;
+        points
     }
 
-    pub fn print(&self, remove: bool, board: &Board) {
-        generic::set_color(self.color);
+    /// Print or remove Tet, and its ghost if enabled
+    pub fn print(&self, remove: bool, game_controller: &GameController) {
+        let board = &game_controller.board;
 
-        let points = self.points_pos();
-        for i in 0..=3 {
-            let y: i16 = points[i].y;
-            if y < 0 {
-                continue;
+        if game_controller.config.display.ghost {
+            let mut ghost = self.clone();
+            ghost.pos.y += self.drop_distance(game_controller);
+
+            if ghost.pos.y != self.pos.y {
+                let glyph = if remove { board.get_empty_str() } else { board.get_ghost_str().to_string() };
+                ghost.print_points(&glyph, board);
             }
+        }
 
-            generic::move_cursor(
-                points[i].x_width() as u16 + board.get_offset_x(),
-                y as u16 + board.get_offset_y()
-            );
+        let glyph = if remove { board.get_empty_str() } else { board.get_block_str(Some(self.kind)).to_string() };
+        self.print_points(&glyph, board);
+    }
 
-            if remove {
-                print!("  ");
-            } else {
-                print!("[]");
-            }
+    fn print_points(&self, glyph: &str, board: &Board) {
+        generic::set_color(self.color);
 
-            generic::move_cursor(0, 0);
+        for point in self.points_pos() {
+            board.print_cell(point.x, point.y, glyph);
         }
 
         generic::set_color(Color::Reset);
+        generic::move_cursor(0, 0);
     }
 
     /// Translate if there's no collision
     ///
     /// Returns true on success or false if unable to move
     pub fn translate(&mut self, x: i16, y: i16, game_controller: &GameController) -> bool {
-        if game_controller.collision_check(self.points_pos(), x, y) {
+        if game_controller.collision_check(&self.points_pos(), x, y) {
             return false;
         }
 
-        self.print(true, &game_controller.board);
+        self.print(true, game_controller);
         self.pos.x += x;
         self.pos.y += y;
-        self.print(false, &game_controller.board);
+        self.rotated_last = false;
+        self.print(false, game_controller);
 
-        return true;
+        true
     }
 
-    pub fn rotate(&mut self, game_controller: &GameController) {
-        if self.allowed_flips == 0 {
+    /// Rotate if there's room, possibly moving Tet a bit to make room
+    pub fn rotate(&mut self, clockwise: bool, game_controller: &GameController) {
+        let Some(rotated) = self.rotated(clockwise, game_controller) else {
+            // Could not rotate
             return;
-        }
+        };
 
-        let mut clone = self.clone();
+        // Update and print `self`
+        self.print(true, game_controller);
+        self.flips = rotated.flips;
+        self.model = rotated.model;
+        self.pivot = rotated.pivot;
+        self.pos = rotated.pos;
+        self.rotated_last = true;
+        self.print(false, game_controller);
+    }
 
-        let reset_flip = clone.allowed_flips == clone.flips;
+    /// Rotations from spawn rotation
+    pub fn get_flips(&self) -> u8 {
+        self.flips
+    }
 
-        if reset_flip {
-            for _ in 0..clone.flips {
-                clone.rotate_model(clone.rotate_clockwise);
-            }
+    /// Copy of Tet rotated by rotation system of config, or `None` if there's no room
+    pub fn rotated(&self, clockwise: bool, game_controller: &GameController) -> Option<Tet> {
+        if self.allowed_flips == 0 {
+            return None;
+        }
+
+        if game_controller.config.gameplay.rotation == "srs" {
+            self.rotate_srs(clockwise, game_controller)
         } else {
-            clone.rotate_model(!clone.rotate_clockwise);
+            self.rotate_classic(clockwise, game_controller)
         }
+    }
+
+    /// Rotate between `allowed_flips` rotations, moving up to 2 columns sideways and 2 rows up to fit
+    fn rotate_classic(&self, clockwise: bool, game_controller: &GameController) -> Option<Tet> {
+        let mut clone = self.clone();
+        clone.turn_classic(clockwise);
 
         // Help player by getting closest free position
-        let mut success = false;
-        for y in 0..=2 {
-            for x in 0..=2 {
-                if !game_controller.collision_check(clone.points_pos(), x, -y) {
+        for y in (0..=2).map(|y| y * self.scale) {
+            for x in (0..=2).map(|x| x * self.scale) {
+                if !game_controller.collision_check(&clone.points_pos(), x, -y) {
                     clone.pos.x += x;
-                    success = true;
-                    break;
+                    clone.pos.y -= y;
+                    return Some(clone);
                 }
 
-                if !game_controller.collision_check(clone.points_pos(), -x, -y) {
+                if !game_controller.collision_check(&clone.points_pos(), -x, -y) {
                     clone.pos.x -= x;
-                    success = true;
-                    break;
+                    clone.pos.y -= y;
+                    return Some(clone);
                 }
             }
+        }
+
+        None
+    }
 
-            if success {
+    /// Rotate through all 4 rotations, trying wall kicks of the piece set in order to fit
+    fn rotate_srs(&self, clockwise: bool, game_controller: &GameController) -> Option<Tet> {
+        let mut clone = self.clone();
+        clone.turn_srs(clockwise);
+
+        for (x, y) in piece_set::get().get_kicks(self.kind, self.flips, clockwise).iter().map(|(x, y)| (x * self.scale, y * self.scale)) {
+            if !game_controller.collision_check(&clone.points_pos(), x, -y) {
+                clone.pos.x += x;
                 clone.pos.y -= y;
-                break;
+                return Some(clone);
             }
         }
 
-        if !success {
-            // Could not rotate
-            return;
-        }
+        None
+    }
 
-        // Update and print `self`
-        self.print(true, &game_controller.board);
-        if !reset_flip {
+    /// Turn model to next rotation of classic rotation, without moving to fit
+    fn turn_classic(&mut self, clockwise: bool) {
+        // Pieces only have `allowed_flips` rotations, then return to spawn rotation
+        if clockwise && self.flips == self.allowed_flips {
+            for _ in 0..self.flips {
+                self.rotate_model(self.rotate_clockwise);
+            }
+            self.flips = 0;
+        } else if clockwise {
+            self.rotate_model(!self.rotate_clockwise);
             self.flips += 1;
+        } else if self.flips == 0 {
+            for _ in 0..self.allowed_flips {
+                self.rotate_model(!self.rotate_clockwise);
+            }
+            self.flips = self.allowed_flips;
         } else {
-            self.flips = 0;
+            self.rotate_model(self.rotate_clockwise);
+            self.flips -= 1;
         }
-        self.model = clone.model;
-        self.pivot = clone.pivot;
-        self.pos = clone.pos;
-        self.print(false, &game_controller.board);
     }
 
-    /// Move tet down and place if able
+    /// Turn model to next rotation of SRS, without moving to fit
     ///
-    /// If unable to place or move, return false, otherwise return true
-    pub fn move_down(&mut self, game_controller: &mut GameController) -> bool {
-        if !self.translate(0, 1, &game_controller) {
-            if !self.place(&mut game_controller.occupied) {
-                return false;
+    /// Uses the SRS states of the piece, or turns the model around its pivot if it has none
+    fn turn_srs(&mut self, clockwise: bool) {
+        self.flips = if clockwise { (self.flips + 1) % 4 } else { (self.flips + 3) % 4 };
+
+        match &piece_set::get().get_piece(self.kind).srs {
+            Some(srs) => self.model = Tet::state_model(&srs.states[self.flips as usize]),
+            // Screen y points down, so a clockwise turn on screen is a counterclockwise turn of the model
+            None => self.rotate_model(!clockwise),
+        }
+    }
+
+    fn state_model(cells: &[(i16, i16)]) -> Vec<Point> {
+        cells.iter().map(|(x, y)| Point::new(*x, *y)).collect()
+    }
+
+    /// Tet of `kind` covering exactly `cells`, in a rotation reachable from spawn
+    pub fn at_cells(kind: TetKind, cells: &[(i16, i16)], game_controller: &GameController) -> Option<Tet> {
+        let mut tet = Tet::new(kind, game_controller);
+        if cells.len() != tet.points_pos().len() {
+            return None;
+        }
+
+        let mut target = cells.to_vec();
+        target.sort();
+        let srs = game_controller.config.gameplay.rotation == "srs";
+
+        for _ in 0..4 {
+            let mut tet_cells: Vec<(i16, i16)> = tet.points_pos().iter().map(|point| (point.x, point.y)).collect();
+            tet_cells.sort();
+
+            // Cells are sorted, so the first of each are the same block if shapes match
+            let (x, y) = (target[0].0 - tet_cells[0].0, target[0].1 - tet_cells[0].1);
+            if tet_cells.iter().map(|(cell_x, cell_y)| (cell_x + x, cell_y + y)).eq(target.iter().copied()) {
+                tet.pos.x += x;
+                tet.pos.y += y;
+                return Some(tet);
             }
 
-            game_controller.place_tet();
+            if srs { tet.turn_srs(true) } else { tet.turn_classic(true) }
+        }
+
+        None
+    }
+
+    /// Move tet down, or place it if it can't move
+    ///
+    /// The next Tet isn't taken here, so the caller can wait for cleared rows to collapse first
+    pub fn move_down(&mut self, game_controller: &mut GameController) -> Fall {
+        if self.translate(0, self.scale, game_controller) {
+            return Fall::Moved;
+        }
+
+        if !self.place(game_controller) || !game_controller.place_tet(self) {
+            return Fall::ToppedOut;
+        }
+
+        Fall::Placed
+    }
 
-            *self = Tet::new_random();
+    /// T was rotated into place with at least 3 of the 4 corners around its center block blocked
+    pub fn is_t_spin(&self, game_controller: &GameController) -> bool {
+        if self.kind.name() != "T" || !self.rotated_last {
+            return false;
         }
 
-        return true;
+        let corner = Point::new(0, 0);
+        let blocked_corners = [(-1, -1), (self.scale, -1), (-1, self.scale), (self.scale, self.scale)].iter()
+            .filter(|(x, y)| game_controller.collision_check(&[corner], self.pos.x + x, self.pos.y + y))
+            .count();
+
+        blocked_corners >= 3
+    }
+
+    /// Move tet down as far as possible and place it
+    pub fn hard_drop(&mut self, game_controller: &mut GameController) -> Fall {
+        // Dropping nothing isn't a move, so a rotation into place still counts for T-spins
+        let distance = self.drop_distance(game_controller);
+        if distance > 0 {
+            self.translate(0, distance, game_controller);
+        }
+
+        self.move_down(game_controller)
+    }
+
+    /// How far tet can move down before colliding, in whole steps
+    pub fn drop_distance(&self, game_controller: &GameController) -> i16 {
+        let mut distance = 0;
+        while !game_controller.collision_check(&self.points_pos(), 0, distance + self.scale) {
+            distance += self.scale;
+        }
+
+        distance
     }
 
     fn rotate_model(&mut self, clockwise: bool) {
-        for i in 0..=3 {
-            let x: i16 = self.model[i].x;
-            let y = self.model[i].y;
+        for point in &mut self.model {
+            let x: i16 = point.x;
+            let y = point.y;
 
             if clockwise {
-                self.model[i].x = y;
-                self.model[i].y = -x;
+                point.x = y;
+                point.y = -x;
             } else {
-                self.model[i].x = -y;
-                self.model[i].y = x;
+                point.x = -y;
+                point.y = x;
             }
         }
 
@@ -339,10 +432,123 @@ impl Tet {
     }
 }
 
-impl Copy for Tet {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, generic};
+
+    fn srs_game() -> GameController {
+        generic::set_headless(true);
+        let mut config = Config::default();
+        config.gameplay.rotation = String::from("srs");
+        GameController::new(&config)
+    }
+
+    fn cells(tet: &Tet) -> Vec<(i16, i16)> {
+        let mut cells: Vec<(i16, i16)> = tet.points_pos().iter().map(|point| (point.x, point.y)).collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn srs_pieces_spawn_flat_left_of_center() {
+        let game = srs_game();
+
+        let t = Tet::new(TetKind::by_name("T").unwrap(), &game);
+        assert_eq!(cells(&t), vec![(3, -1), (4, -2), (4, -1), (5, -1)]);
+
+        let i = Tet::new(TetKind::by_name("I").unwrap(), &game);
+        assert_eq!(cells(&i), vec![(3, -1), (4, -1), (5, -1), (6, -1)]);
+    }
+
+    #[test]
+    fn srs_i_turns_around_center_of_its_box() {
+        let game = srs_game();
+        let mut i = Tet::new(TetKind::by_name("I").unwrap(), &game);
+        i.pos.y += 5;
+
+        let right = i.rotated(true, &game).unwrap();
+        assert_eq!(cells(&right), vec![(5, 3), (5, 4), (5, 5), (5, 6)]);
+
+        let reverse = right.rotated(true, &game).unwrap();
+        assert_eq!(cells(&reverse), vec![(3, 5), (4, 5), (5, 5), (6, 5)]);
+    }
+
+    #[test]
+    fn srs_kicks_off_the_wall() {
+        let game = srs_game();
+        let mut t = Tet::new(TetKind::by_name("T").unwrap(), &game);
+        t.pos.y += 5;
+        t = t.rotated(false, &game).unwrap();
+        // Pointing left against the right wall
+        t.pos.x = 9;
+
+        // Turning back to spawn doesn't fit in place, so the first kick moves it left
+        let spawn = t.rotated(true, &game).unwrap();
+        assert_eq!(cells(&spawn), vec![(7, 4), (8, 3), (8, 4), (9, 4)]);
+    }
+
+    #[test]
+    fn big_piece_reaches_walls_and_floor() {
+        generic::set_headless(true);
+        let mut config = Config::default();
+        config.gameplay.big = true;
+        // Board center is an odd column, which big pieces would otherwise spawn on
+        config.display.board_width = 12;
+        config.display.board_height = 21;
+        let mut game = GameController::new(&config);
+        let mut o = Tet::new(TetKind::by_name("O").unwrap(), &game);
+
+        while o.drop_distance(&game) > 0 {
+            assert!(o.move_down(&mut game) == Fall::Moved);
+        }
+        let mut right = o.clone();
+        while right.translate(right.get_step(), 0, &game) {}
+        while o.translate(-o.get_step(), 0, &game) {}
+
+        let expected: Vec<(i16, i16)> = (0..4).flat_map(|x| (17..21).map(move |y| (x, y))).collect();
+        assert_eq!(cells(&o), expected);
+        assert_eq!(cells(&right).last(), Some(&(11, 20)));
+    }
+
+    #[test]
+    fn lock_out_needs_piece_entirely_hidden_unless_partial() {
+        generic::set_headless(true);
+        let mut game = GameController::new(&Config::default());
+        let mut t = Tet::new(TetKind::by_name("T").unwrap(), &game);
+
+        assert!(cells(&t).iter().all(|(_, y)| *y < 0));
+        assert!(t.is_locked_out(false));
+        assert!(!t.place(&mut game));
+        assert!(game.occupied.is_empty());
+
+        t.pos.y += 1;
+        assert!(!t.is_locked_out(false));
+        assert!(t.is_locked_out(true));
+
+        t.pos.y += 1;
+        assert!(!t.is_locked_out(true));
+    }
 
-impl Clone for Tet {
-    fn clone(&self) -> Self {
-        *self
+    #[test]
+    fn t_rotated_into_slot_and_hard_dropped_is_t_spin() {
+        generic::set_headless(true);
+        let mut game = GameController::new(&Config::default());
+        let mut t = Tet::new(TetKind::by_name("T").unwrap(), &game);
+
+        // Bottom row with a hole under the slot, and a block over the slot's left corner
+        let mut blocks: Vec<Point> = (0..10).filter(|x| *x != 1).map(|x| Point::new_block(x, 19, Color::White, t.kind)).collect();
+        blocks.push(Point::new_block(0, 17, Color::White, t.kind));
+        game.occupied.add_points(&blocks);
+
+        t.pos.y += 5;
+        t.rotate(true, &game);
+        t.pos = Point::new(1, 18);
+        t.rotate(false, &game);
+        assert_eq!(cells(&t), vec![(0, 18), (1, 18), (1, 19), (2, 18)]);
+
+        assert!(t.hard_drop(&mut game) == Fall::Placed);
+        // A single clears with 2 lines of garbage only as a T-spin
+        assert_eq!(game.take_sent_garbage(), 2);
     }
 }
@@ -0,0 +1,125 @@
+use crossterm::event::KeyCode;
+use crate::{generic, input::Input};
+
+pub enum TextFieldEvent {
+    None,
+    Changed,
+    Submit,
+    Cancel,
+}
+
+/// Single line text entry with a length limit and a set of allowed characters
+///
+/// Values longer than the view width scroll to keep the cursor in view
+pub struct TextField {
+    chars: Vec<char>,
+    cursor: usize,
+    max_len: usize,
+    allowed: fn(char) -> bool,
+    /// Columns shown on screen
+    view_width: usize,
+    /// First character shown
+    scroll: usize,
+}
+
+impl TextField {
+    pub fn new(max_len: usize, allowed: fn(char) -> bool) -> TextField {
+        TextField {
+            chars: Vec::new(),
+            cursor: 0,
+            max_len,
+            allowed,
+            view_width: max_len,
+            scroll: 0,
+        }
+    }
+
+    /// Show at most `view_width` columns, for fields that allow more characters than fit on screen
+    pub fn set_view_width(&mut self, view_width: usize) {
+        self.view_width = view_width.clamp(1, self.max_len.max(1));
+        self.scroll_to_cursor();
+    }
+
+    /// Replace value and put cursor at the end
+    ///
+    /// Characters that aren't allowed are skipped, and the value is cut at `max_len`
+    pub fn set_value(&mut self, value: &str) {
+        self.chars = value.chars()
+            .filter(|c| (self.allowed)(*c))
+            .take(self.max_len)
+            .collect();
+        self.cursor = self.chars.len();
+        self.scroll_to_cursor();
+    }
+
+    pub fn get_value(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Part of value that fits in the view width
+    pub fn get_visible(&self) -> String {
+        self.chars.iter().skip(self.scroll).take(self.view_width).collect()
+    }
+
+    /// Scroll just enough to show the cursor, which is after the last character at the end of the value
+    fn scroll_to_cursor(&mut self) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + self.view_width {
+            self.scroll = self.cursor + 1 - self.view_width;
+        }
+    }
+
+    /// Handle keys typed this cycle
+    pub fn update(&mut self, input: &Input) -> TextFieldEvent {
+        let mut event = TextFieldEvent::None;
+
+        for code in input.typed_keys() {
+            match code {
+                KeyCode::Enter => return TextFieldEvent::Submit,
+                KeyCode::Esc => return TextFieldEvent::Cancel,
+                KeyCode::Char(c) if self.chars.len() < self.max_len && (self.allowed)(*c) => {
+                    self.chars.insert(self.cursor, *c);
+                    self.cursor += 1;
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::Backspace if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    self.chars.remove(self.cursor);
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::Delete if self.cursor < self.chars.len() => {
+                    self.chars.remove(self.cursor);
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::Left if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::Right if self.cursor < self.chars.len() => {
+                    self.cursor += 1;
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::Home => {
+                    self.cursor = 0;
+                    event = TextFieldEvent::Changed;
+                },
+                KeyCode::End => {
+                    self.cursor = self.chars.len();
+                    event = TextFieldEvent::Changed;
+                },
+                _ => (),
+            }
+        }
+
+        self.scroll_to_cursor();
+        event
+    }
+
+    /// Print visible part of value padded to the view width, and move terminal cursor to text cursor
+    pub fn print(&self, x: u16, y: u16) {
+        generic::move_cursor(x, y);
+        print!("{:<width$}", self.get_visible(), width = self.view_width);
+        generic::move_cursor(x + (self.cursor - self.scroll) as u16, y);
+    }
+}
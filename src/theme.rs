@@ -0,0 +1,169 @@
+use crossterm::style::Color;
+use crate::tet::TetKind;
+
+/// Colors and characters used to draw the game
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Piece colors, by `TetKind::index`, repeating for piece sets with more pieces
+    pub colors: [Color; 7],
+    pub block: &'static str,
+    pub ghost: &'static str,
+    /// Left wall, 2 characters wide
+    pub frame_left: &'static str,
+    /// Right wall, 2 characters wide
+    pub frame_right: &'static str,
+    /// Ceiling, 1 character wide
+    pub frame_top: &'static str,
+    /// Floor, 1 character wide
+    pub frame_bottom: &'static str,
+}
+
+const CLASSIC_COLORS: [Color; 7] = [
+    Color::Blue,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::DarkYellow,
+];
+
+pub const THEMES: [Theme; 5] = [
+    Theme {
+        name: "classic",
+        colors: CLASSIC_COLORS,
+        block: "[]",
+        ghost: "::",
+        frame_left: "<|",
+        frame_right: "|>",
+        frame_top: "_",
+        frame_bottom: "‾",
+    },
+    Theme {
+        name: "solid",
+        colors: CLASSIC_COLORS,
+        block: "██",
+        ghost: "░░",
+        frame_left: " ║",
+        frame_right: "║ ",
+        frame_top: "═",
+        frame_bottom: "═",
+    },
+    Theme {
+        name: "shaded",
+        colors: CLASSIC_COLORS,
+        block: "▓▓",
+        ghost: "░░",
+        frame_left: " ▒",
+        frame_right: "▒ ",
+        frame_top: "▒",
+        frame_bottom: "▒",
+    },
+    Theme {
+        name: "ascii",
+        colors: CLASSIC_COLORS,
+        block: "##",
+        ghost: "..",
+        frame_left: "<|",
+        frame_right: "|>",
+        frame_top: "_",
+        frame_bottom: "-",
+    },
+    Theme {
+        name: "monochrome",
+        colors: [Color::Reset; 7],
+        block: "[]",
+        ghost: "::",
+        frame_left: "<|",
+        frame_right: "|>",
+        frame_top: "_",
+        frame_bottom: "‾",
+    },
+];
+
+/// Glyph per piece kind, repeating for piece sets with more pieces, so pieces can be told apart without color
+pub const PIECE_PATTERNS: [&str; 7] = ["[]", "()", "{}", "<>", "##", "//", "\\\\"];
+
+/// Palettes that can replace theme colors, by piece index
+///
+/// Each has a color for every one of the 18 pentominoes, picked to stay apart both with the color blindness
+/// it's named after and with normal vision. The most distinct colors come first, so smaller sets get them
+pub const PALETTES: [(&str, &[Color]); 2] = [
+    // Blues, oranges and yellows told apart by lightness, without red-green pairs
+    ("deuteranopia", &[
+        Color::Rgb { r: 0x55, g: 0xbb, b: 0xee },
+        Color::Rgb { r: 0xee, g: 0x55, b: 0x00 },
+        Color::Rgb { r: 0x33, g: 0x77, b: 0x88 },
+        Color::Rgb { r: 0xee, g: 0xdd, b: 0xdd },
+        Color::Rgb { r: 0xee, g: 0xee, b: 0x00 },
+        Color::Rgb { r: 0x55, g: 0xaa, b: 0x99 },
+        Color::Rgb { r: 0x88, g: 0x66, b: 0x55 },
+        Color::Rgb { r: 0x33, g: 0x77, b: 0xff },
+        Color::Rgb { r: 0xbb, g: 0xaa, b: 0x88 },
+        Color::Rgb { r: 0xaa, g: 0xee, b: 0xff },
+        Color::Rgb { r: 0xdd, g: 0xee, b: 0xaa },
+        Color::Rgb { r: 0x88, g: 0x66, b: 0x00 },
+        Color::Rgb { r: 0xee, g: 0xaa, b: 0x55 },
+        Color::Rgb { r: 0x88, g: 0x88, b: 0xaa },
+        Color::Rgb { r: 0x22, g: 0x88, b: 0x77 },
+        Color::Rgb { r: 0x66, g: 0x99, b: 0x55 },
+        Color::Rgb { r: 0xdd, g: 0x99, b: 0xcc },
+        Color::Rgb { r: 0x88, g: 0x88, b: 0x77 },
+    ]),
+    // Reds made bright enough to not look black, since protanopia darkens them
+    ("protanopia", &[
+        Color::Rgb { r: 0x55, g: 0xbb, b: 0xee },
+        Color::Rgb { r: 0xff, g: 0x88, b: 0x11 },
+        Color::Rgb { r: 0x99, g: 0x77, b: 0x88 },
+        Color::Rgb { r: 0xbb, g: 0x00, b: 0xdd },
+        Color::Rgb { r: 0xbb, g: 0xbb, b: 0xaa },
+        Color::Rgb { r: 0x99, g: 0xff, b: 0x33 },
+        Color::Rgb { r: 0xff, g: 0x33, b: 0x44 },
+        Color::Rgb { r: 0x55, g: 0xee, b: 0xee },
+        Color::Rgb { r: 0x99, g: 0x77, b: 0xff },
+        Color::Rgb { r: 0xaa, g: 0xee, b: 0xaa },
+        Color::Rgb { r: 0xaa, g: 0x88, b: 0x77 },
+        Color::Rgb { r: 0x55, g: 0xaa, b: 0xaa },
+        Color::Rgb { r: 0x66, g: 0xbb, b: 0x77 },
+        Color::Rgb { r: 0x99, g: 0x77, b: 0x00 },
+        Color::Rgb { r: 0xbb, g: 0x66, b: 0xaa },
+        Color::Rgb { r: 0xcc, g: 0x55, b: 0x66 },
+        Color::Rgb { r: 0xff, g: 0xbb, b: 0x44 },
+        Color::Rgb { r: 0xbb, g: 0xee, b: 0xdd },
+    ]),
+];
+
+pub fn palette_by_name(name: &str) -> Option<&'static [Color]> {
+    PALETTES.iter().find(|(palette_name, _)| *palette_name == name).map(|(_, colors)| *colors)
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Theme> {
+        THEMES.iter().find(|theme| theme.name == name).copied()
+    }
+
+    pub fn piece_color(&self, kind: TetKind) -> Color {
+        self.colors[kind.index() % self.colors.len()]
+    }
+}
+
+/// Fit glyph like "[]" to `width` terminal columns
+///
+/// Outer characters are kept, and the middle is filled with spaces, or with the glyph character if it's uniform
+pub fn fit_glyph(glyph: &str, width: u16) -> String {
+    let chars: Vec<char> = glyph.chars().collect();
+    if chars.len() == width as usize || chars.is_empty() {
+        return String::from(glyph);
+    }
+
+    let first = chars[0];
+    let last = chars[chars.len() - 1];
+    let fill = if first == last { first } else { ' ' };
+
+    if width == 1 {
+        return if first == last { first.to_string() } else { String::from("#") };
+    }
+
+    format!("{}{}{}", first, fill.to_string().repeat(width as usize - 2), last)
+}
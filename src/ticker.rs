@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+use crate::input::Input;
+
+/// Limits how often loops run, so they wait for input or the next tick between cycles instead of spinning
+///
+/// This only paces the loops like a frame limiter. Gravity, key repeat and animations are timed by the clock, so they
+/// don't depend on the tick rate
+pub struct Ticker {
+    /// Time between ticks, or none to only wake up for input
+    interval: Option<Duration>,
+    /// Deadline of the next tick
+    next: Instant,
+}
+
+impl Ticker {
+    pub fn new(ticks_per_sec: u16) -> Ticker {
+        let interval = Duration::from_secs(1) / ticks_per_sec.max(1) as u32;
+        Ticker {
+            interval: Some(interval),
+            next: Instant::now() + interval,
+        }
+    }
+
+    /// Ticker without ticks, for loops that only react to keys, so `wait` blocks until input arrives
+    pub fn until_input() -> Ticker {
+        Ticker {
+            interval: None,
+            next: Instant::now(),
+        }
+    }
+
+    /// Time to wait until the next tick, moving the deadline forward once it has passed, or none without ticks
+    ///
+    /// If ticks were missed, the next tick is a full interval from now rather than catching up
+    pub fn time_left(&mut self) -> Option<Duration> {
+        let interval = self.interval?;
+        let now = Instant::now();
+        if now >= self.next {
+            self.next += interval;
+            if self.next <= now {
+                self.next = now + interval;
+            }
+            return Some(Duration::ZERO);
+        }
+
+        Some(self.next - now)
+    }
+
+    /// Capture `input` until the next tick is due, returning early if input arrives
+    pub fn wait(&mut self, input: &mut Input) {
+        let timeout = self.time_left();
+        input.capture_input(timeout);
+    }
+}
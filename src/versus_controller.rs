@@ -0,0 +1,74 @@
+use crossterm::terminal;
+use crate::{generic, game_controller::GameController, input_controller::InputController, config::Config, action::Action, layout::Layout};
+
+const MODE: &str = "versus";
+
+/// Layouts of left and right player, or an error if the terminal is too narrow
+pub fn get_layouts(config: &Config) -> Result<(Layout, Layout), String> {
+    let (terminal_width, _) = terminal::size().unwrap_or((80, 24));
+    let layout_1 = Layout::new_at(config, MODE, 0, terminal_width / 2);
+    let layout_2 = Layout::new_at(config, MODE, layout_1.get_right_x(), terminal_width / 2);
+    if layout_2.get_right_x() > terminal_width {
+        return Err(format!("Versus needs a terminal at least {} columns wide", layout_2.get_right_x()));
+    }
+
+    Ok((layout_1, layout_2))
+}
+
+/// Two players side by side, sending garbage to each other until one tops out
+pub fn versus_loop(config: &Config, input_controller: &mut InputController) {
+    generic::clear_terminal();
+
+    let (layout_1, layout_2) = match get_layouts(config) {
+        Ok(layouts) => layouts,
+        Err(error) => {
+            generic::error_print(&error);
+            return;
+        },
+    };
+
+    // Same seed, so both players get the same Tets
+    let seed = rand::random();
+    let mut games = [GameController::new_at(config, layout_1, seed), GameController::new_at(config, layout_2, seed)];
+    input_controller.apply_versus_config(config);
+
+    let loser = loop {
+        input_controller.update();
+
+        if input_controller.action_pressed(Action::Quit) {
+            break None;
+        }
+
+        if input_controller.action_pressed(Action::Pause) && !GameController::pause(&mut games, input_controller) {
+            break None;
+        }
+
+        let loser = (0..games.len()).find(|player| {
+            games[*player].count_inputs(&input_controller.pressed_actions(*player));
+            let actions = input_controller.player_actions(*player);
+            if !games[*player].step(&actions) {
+                return true;
+            }
+
+            let garbage = games[*player].take_sent_garbage();
+            games[1 - *player].add_garbage(garbage);
+            false
+        });
+        if loser.is_some() {
+            break loser;
+        }
+
+        input_controller.end_update();
+    };
+
+    input_controller.apply_config(config);
+
+    let Some(loser) = loser else {
+        return;
+    };
+
+    games[loser].print_message(2, "Topped out");
+    games[1 - loser].print_message(2, &format!("Player {} wins!", 2 - loser));
+    games[1 - loser].print_message(4, "Press ENTER");
+    InputController::wait_for_enter();
+}